@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use clap::{builder::PossibleValue, Parser, ValueEnum};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Algorithm {
+	AntSystem,
+	MaxMin,
+	AntColonySystem,
+}
+
+impl ValueEnum for Algorithm {
+	fn value_variants<'a>() -> &'a [Self] {
+		&[
+			Algorithm::AntSystem,
+			Algorithm::MaxMin,
+			Algorithm::AntColonySystem,
+		]
+	}
+
+	fn to_possible_value(&self) -> Option<PossibleValue> {
+		match self {
+			Algorithm::AntSystem => Some(PossibleValue::new("as")),
+			Algorithm::MaxMin => Some(PossibleValue::new("mmas")),
+			Algorithm::AntColonySystem => Some(PossibleValue::new("acs")),
+		}
+	}
+}
+
+#[derive(Parser)]
+#[command(
+	version,
+	about = "Ant colony optimisation for TSPLIB travelling salesman instances.
+
+Prints the best tour length of every iteration as CSV."
+)]
+pub struct Args {
+	/// Path to the TSPLIB .tsp file.
+	pub instance: PathBuf,
+
+	/// The ACO algorithm.
+	#[arg(short, long, default_value = "mmas")]
+	pub algorithm: Algorithm,
+
+	/// Number of iterations.
+	#[arg(short, long, value_name = "ITER", default_value_t = 1000)]
+	pub iter: usize,
+
+	/// Number of ants (default: depends on the algorithm).
+	#[arg(long)]
+	pub ants: Option<usize>,
+
+	/// Influence of the pheromone (default: depends on the algorithm).
+	#[arg(long)]
+	pub alpha: Option<f64>,
+
+	/// Influence of the distance (default: depends on the algorithm).
+	#[arg(long)]
+	pub beta: Option<f64>,
+
+	/// Pheromone evaporation rate (default: depends on the algorithm).
+	#[arg(long)]
+	pub rho: Option<f64>,
+
+	/// Seed for the random number generator.
+	#[arg(long, default_value_t = 0)]
+	pub seed: u64,
+
+	/// Where to save an image of the best tour.
+	#[arg(short, long, value_name = "PATH")]
+	pub output: Option<PathBuf>,
+
+	/// The width of the image.
+	#[arg(short, long, default_value_t = 800)]
+	pub width: u32,
+
+	/// The height of the image.
+	#[arg(short = 'H', long, default_value_t = 800)]
+	pub height: u32,
+}
@@ -0,0 +1,67 @@
+mod cli;
+
+use std::{
+	fs::File,
+	io::{self, BufWriter},
+	path::Path,
+	process::ExitCode,
+};
+
+use aco::{
+	colony::{write_log_csv, Colony, Params},
+	draw::draw_tour,
+	tsp::Instance,
+};
+use clap::Parser as _;
+use cli::{Algorithm, Args};
+
+fn main() -> ExitCode {
+	let args = Args::parse();
+
+	let instance = match Instance::load(&args.instance) {
+		Ok(instance) => instance,
+		Err(err) => {
+			eprintln!("Could not load {}: {err}", args.instance.display());
+			return ExitCode::FAILURE;
+		}
+	};
+
+	let mut params = match args.algorithm {
+		Algorithm::AntSystem => Params::ant_system(),
+		Algorithm::MaxMin => Params::max_min(),
+		Algorithm::AntColonySystem => Params::ant_colony_system(),
+	};
+	params.n_ants = args.ants.or(params.n_ants);
+	params.alpha = args.alpha.unwrap_or(params.alpha);
+	params.beta = args.beta.unwrap_or(params.beta);
+	params.rho = args.rho.unwrap_or(params.rho);
+
+	let mut colony = Colony::new(&instance, params, args.seed);
+	colony.run(args.iter, |_| {});
+	if let Err(err) = write_log_csv(&mut io::stdout().lock(), colony.log()) {
+		eprintln!("Could not write the log: {err}");
+		return ExitCode::FAILURE;
+	}
+
+	if let (Some(path), Some((tour, length))) = (&args.output, colony.best()) {
+		let mut frame = vec![0; (args.width * args.height * 4) as usize];
+		draw_tour(&instance, tour, &mut frame, args.width, args.height);
+		if let Err(err) = save_image(&frame, path, &args) {
+			eprintln!("Could not save {}: {err}", path.display());
+			return ExitCode::FAILURE;
+		}
+		eprintln!("Saved tour of length {length} to {}", path.display());
+	}
+	ExitCode::SUCCESS
+}
+
+fn save_image(frame: &[u8], path: &Path, args: &Args) -> io::Result<()> {
+	let file = BufWriter::new(File::create(path)?);
+
+	let mut png_encoder = png::Encoder::new(file, args.width, args.height);
+	png_encoder.set_color(png::ColorType::Rgba);
+	png_encoder.set_depth(png::BitDepth::Eight);
+	let mut png_writer = png_encoder.write_header()?;
+	png_writer.write_image_data(frame)?;
+	Ok(())
+}
@@ -0,0 +1,357 @@
+use std::io::{self, Write};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::tsp::Instance;
+
+/// The ACO algorithm, which differs in how ants choose the next city and how
+/// the pheromone is updated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Variant {
+	/// The original Ant System: all ants deposit pheromone, proportional to
+	/// the quality of their tour.
+	AntSystem,
+
+	/// MAX–MIN Ant System: only the best ant of each iteration deposits
+	/// pheromone, and the pheromone is kept between bounds to prevent
+	/// stagnation. `p_best` is the probability that the best tour is
+	/// constructed once the pheromone has converged, which sets the lower
+	/// bound.
+	MaxMin { p_best: f64 },
+
+	/// Ant Colony System: with probability `q0`, ants greedily take the best
+	/// edge. Ants remove a fraction `xi` of the pheromone on edges as they use
+	/// them (local update), and only the best-so-far tour receives pheromone.
+	AntColonySystem { q0: f64, xi: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Params {
+	/// The number of ants, or [`None`] to use one ant per city.
+	pub n_ants: Option<usize>,
+
+	/// The relative influence of the pheromone.
+	pub alpha: f64,
+
+	/// The relative influence of the heuristic information (the inverse of
+	/// the distance).
+	pub beta: f64,
+
+	/// The evaporation rate of the pheromone.
+	pub rho: f64,
+
+	pub variant: Variant,
+}
+
+impl Params {
+	/// The recommended parameters for Ant System.
+	#[must_use]
+	pub fn ant_system() -> Self {
+		Self {
+			n_ants: None,
+			alpha: 1.0,
+			beta: 3.0,
+			rho: 0.5,
+			variant: Variant::AntSystem,
+		}
+	}
+
+	/// The recommended parameters for MAX–MIN Ant System.
+	#[must_use]
+	pub fn max_min() -> Self {
+		Self {
+			n_ants: None,
+			alpha: 1.0,
+			beta: 2.0,
+			rho: 0.02,
+			variant: Variant::MaxMin { p_best: 0.05 },
+		}
+	}
+
+	/// The recommended parameters for Ant Colony System.
+	#[must_use]
+	pub fn ant_colony_system() -> Self {
+		Self {
+			n_ants: Some(10),
+			alpha: 1.0,
+			beta: 2.0,
+			rho: 0.1,
+			variant: Variant::AntColonySystem { q0: 0.9, xi: 0.1 },
+		}
+	}
+}
+
+/// Tour lengths of a single iteration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IterationStats {
+	pub iteration: usize,
+
+	/// The length of the best tour found so far.
+	pub best: f64,
+
+	/// The length of the best tour of this iteration, or NaN without ants.
+	pub iteration_best: f64,
+
+	/// The mean length of the tours of this iteration.
+	pub mean: f64,
+}
+
+pub struct Colony<'a> {
+	instance: &'a Instance,
+	params: Params,
+	rng: StdRng,
+
+	/// The pheromone on every edge, indexed by `a * n + b` (symmetric).
+	pheromone: Vec<f64>,
+
+	/// The heuristic information `(1 / distance)^beta` of every edge.
+	heuristic: Vec<f64>,
+
+	/// The initial pheromone, used by the local update of Ant Colony System.
+	tau0: f64,
+
+	best: Option<(Vec<usize>, f64)>,
+	log: Vec<IterationStats>,
+}
+
+impl<'a> Colony<'a> {
+	/// Creates a colony for `instance`, with the initial pheromone derived
+	/// from the length of the nearest-neighbour tour as recommended for each
+	/// variant.
+	#[must_use]
+	pub fn new(instance: &'a Instance, params: Params, seed: u64) -> Self {
+		let n = instance.n_cities();
+		let n_ants = params.n_ants.unwrap_or(n).max(1) as f64;
+		let nn_length = instance
+			.tour_length(&instance.nearest_neighbour_tour())
+			.max(f64::MIN_POSITIVE);
+		let tau0 = match params.variant {
+			Variant::AntSystem => n_ants / nn_length,
+			Variant::MaxMin { .. } => 1.0 / (params.rho * nn_length),
+			Variant::AntColonySystem { .. } => 1.0 / (n as f64 * nn_length),
+		};
+		let heuristic = (0..n * n)
+			.map(|i| {
+				let distance = instance.distance(i / n, i % n);
+				(1.0 / distance.max(f64::EPSILON)).powf(params.beta)
+			})
+			.collect();
+
+		Self {
+			instance,
+			params,
+			rng: StdRng::seed_from_u64(seed),
+			pheromone: vec![tau0; n * n],
+			heuristic,
+			tau0,
+			best: None,
+			log: Vec::new(),
+		}
+	}
+
+	/// Returns the best tour found so far and its length.
+	#[must_use]
+	pub fn best(&self) -> Option<&(Vec<usize>, f64)> {
+		self.best.as_ref()
+	}
+
+	/// Returns the statistics of every iteration so far.
+	#[must_use]
+	pub fn log(&self) -> &[IterationStats] {
+		&self.log
+	}
+
+	#[must_use]
+	pub fn pheromone(&self, a: usize, b: usize) -> f64 {
+		self.pheromone[a * self.instance.n_cities() + b]
+	}
+
+	/// Runs `iterations` iterations, calling `on_iteration` after each one.
+	pub fn run<L>(&mut self, iterations: usize, mut on_iteration: L)
+	where
+		L: FnMut(&IterationStats),
+	{
+		for _ in 0..iterations {
+			let stats = self.iterate();
+			on_iteration(&stats);
+		}
+	}
+
+	/// Lets every ant construct a tour, and updates the pheromone.
+	pub fn iterate(&mut self) -> IterationStats {
+		let n_ants = self.params.n_ants.unwrap_or(self.instance.n_cities());
+		let tours: Vec<(Vec<usize>, f64)> = (0..n_ants)
+			.map(|_| {
+				let tour = self.construct_tour();
+				let length = self.instance.tour_length(&tour);
+				(tour, length)
+			})
+			.collect();
+
+		let iteration_best = tours
+			.iter()
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))
+			.cloned();
+		// Without ants there is no tour to deposit pheromone on
+		if let Some(iteration_best) = &iteration_best {
+			if self
+				.best
+				.as_ref()
+				.is_none_or(|(_, length)| iteration_best.1 < *length)
+			{
+				self.best = Some(iteration_best.clone());
+			}
+			self.update_pheromone(&tours, iteration_best);
+		}
+
+		let stats = IterationStats {
+			iteration: self.log.len(),
+			best: self.best.as_ref().map_or(f64::NAN, |(_, length)| *length),
+			iteration_best: iteration_best.map_or(f64::NAN, |(_, length)| length),
+			mean: tours.iter().map(|(_, length)| length).sum::<f64>() / tours.len().max(1) as f64,
+		};
+		self.log.push(stats);
+		stats
+	}
+
+	/// Returns the attractiveness `tau^alpha * eta^beta` of the edge `a -> b`.
+	#[inline]
+	fn choice(&self, a: usize, b: usize) -> f64 {
+		let i = a * self.instance.n_cities() + b;
+		self.pheromone[i].powf(self.params.alpha) * self.heuristic[i]
+	}
+
+	/// Constructs a tour starting at a random city.
+	fn construct_tour(&mut self) -> Vec<usize> {
+		let n = self.instance.n_cities();
+		let mut visited = vec![false; n];
+		let mut tour = Vec::with_capacity(n);
+		let mut current = self.rng.gen_range(0..n);
+		visited[current] = true;
+		tour.push(current);
+
+		while tour.len() < n {
+			let next = self.choose_next(current, &visited);
+			if let Variant::AntColonySystem { xi, .. } = self.params.variant {
+				let tau = (1.0 - xi) * self.pheromone(current, next) + xi * self.tau0;
+				self.set_pheromone(current, next, tau);
+			}
+			visited[next] = true;
+			tour.push(next);
+			current = next;
+		}
+
+		if let Variant::AntColonySystem { xi, .. } = self.params.variant {
+			// Also close the tour
+			let first = tour[0];
+			let tau = (1.0 - xi) * self.pheromone(current, first) + xi * self.tau0;
+			self.set_pheromone(current, first, tau);
+		}
+		tour
+	}
+
+	/// Chooses the next city from `current` using the random proportional
+	/// rule (or, for Ant Colony System, the pseudo-random proportional rule).
+	fn choose_next(&mut self, current: usize, visited: &[bool]) -> usize {
+		let candidates = (0..visited.len()).filter(|&city| !visited[city]);
+
+		if let Variant::AntColonySystem { q0, .. } = self.params.variant {
+			if self.rng.gen::<f64>() < q0 {
+				return candidates
+					.max_by(|&a, &b| self.choice(current, a).total_cmp(&self.choice(current, b)))
+					.unwrap_or(current);
+			}
+		}
+
+		let weights: Vec<(usize, f64)> = candidates
+			.map(|city| (city, self.choice(current, city)))
+			.collect();
+		let total: f64 = weights.iter().map(|(_, weight)| weight).sum();
+		if total <= 0.0 || !total.is_finite() {
+			return weights[self.rng.gen_range(0..weights.len())].0;
+		}
+		let mut target = self.rng.gen::<f64>() * total;
+		for &(city, weight) in &weights {
+			target -= weight;
+			if target <= 0.0 {
+				return city;
+			}
+		}
+		weights.last().map_or(current, |(city, _)| *city)
+	}
+
+	#[inline]
+	fn set_pheromone(&mut self, a: usize, b: usize, value: f64) {
+		let n = self.instance.n_cities();
+		self.pheromone[a * n + b] = value;
+		self.pheromone[b * n + a] = value;
+	}
+
+	/// Adds `amount` of pheromone to every edge of `tour`.
+	fn deposit(&mut self, tour: &[usize], amount: f64) {
+		for (&a, &b) in tour.iter().zip(tour.iter().cycle().skip(1)) {
+			self.set_pheromone(a, b, self.pheromone(a, b) + amount);
+		}
+	}
+
+	fn update_pheromone(
+		&mut self,
+		tours: &[(Vec<usize>, f64)],
+		iteration_best: &(Vec<usize>, f64),
+	) {
+		let rho = self.params.rho;
+		match self.params.variant {
+			Variant::AntSystem => {
+				self.evaporate();
+				for (tour, length) in tours {
+					self.deposit(tour, 1.0 / length);
+				}
+			}
+			Variant::MaxMin { p_best } => {
+				self.evaporate();
+				self.deposit(&iteration_best.0, 1.0 / iteration_best.1);
+
+				let best_length = self.best.as_ref().map_or(iteration_best.1, |(_, l)| *l);
+				let tau_max = 1.0 / (rho * best_length);
+				let n = self.instance.n_cities() as f64;
+				let p_dec = p_best.powf(1.0 / n);
+				let tau_min = (tau_max * (1.0 - p_dec) / ((n / 2.0 - 1.0) * p_dec)).min(tau_max);
+				for tau in &mut self.pheromone {
+					*tau = tau.clamp(tau_min, tau_max);
+				}
+			}
+			Variant::AntColonySystem { .. } => {
+				let Some((tour, length)) = self.best.clone() else {
+					return;
+				};
+				for (&a, &b) in tour.iter().zip(tour.iter().cycle().skip(1)) {
+					let tau = (1.0 - rho) * self.pheromone(a, b) + rho / length;
+					self.set_pheromone(a, b, tau);
+				}
+			}
+		}
+	}
+
+	fn evaporate(&mut self) {
+		for tau in &mut self.pheromone {
+			*tau *= 1.0 - self.params.rho;
+		}
+	}
+}
+
+/// Writes the statistics of every iteration as CSV, with the columns
+/// `iteration,best,iteration_best,mean`.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn write_log_csv<Wr: Write>(out: &mut Wr, log: &[IterationStats]) -> io::Result<()> {
+	writeln!(out, "iteration,best,iteration_best,mean")?;
+	for stats in log {
+		writeln!(
+			out,
+			"{},{},{},{}",
+			stats.iteration, stats.best, stats.iteration_best, stats.mean
+		)?;
+	}
+	Ok(())
+}
@@ -0,0 +1,96 @@
+use crate::tsp::Instance;
+
+const BACKGROUND: [u8; 4] = [0x00, 0x00, 0x00, 0xff];
+const EDGE: [u8; 4] = [0xcc, 0xcc, 0xcc, 0xff];
+const CITY: [u8; 4] = [0xff, 0x44, 0x44, 0xff];
+
+/// The number of pixels between the outermost cities and the edge of the
+/// frame.
+const MARGIN: f64 = 10.0;
+
+/// Draws the cities of `instance` and the closed `tour` through them into an
+/// RGBA `frame` of `width`×`height` pixels, scaled to fit. The y axis points
+/// up, as in the TSPLIB coordinates.
+pub fn draw_tour(instance: &Instance, tour: &[usize], frame: &mut [u8], width: u32, height: u32) {
+	for pixel in frame.chunks_exact_mut(4) {
+		pixel.copy_from_slice(&BACKGROUND);
+	}
+
+	let to_screen = screen_transform(instance, width, height);
+	for (&a, &b) in tour.iter().zip(tour.iter().cycle().skip(1)) {
+		draw_line(
+			frame,
+			width,
+			height,
+			to_screen(instance.cities[a]),
+			to_screen(instance.cities[b]),
+		);
+	}
+	for &city in &instance.cities {
+		let (x, y) = to_screen(city);
+		for dy in -1..=1 {
+			for dx in -1..=1 {
+				set_pixel(frame, width, height, (x + dx, y + dy), CITY);
+			}
+		}
+	}
+}
+
+/// Returns a function mapping city coordinates to pixel coordinates, keeping
+/// the aspect ratio.
+fn screen_transform(
+	instance: &Instance,
+	width: u32,
+	height: u32,
+) -> impl Fn((f64, f64)) -> (i64, i64) {
+	let (mut min_x, mut min_y) = (f64::INFINITY, f64::INFINITY);
+	let (mut max_x, mut max_y) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+	for &(x, y) in &instance.cities {
+		min_x = min_x.min(x);
+		min_y = min_y.min(y);
+		max_x = max_x.max(x);
+		max_y = max_y.max(y);
+	}
+	let scale_x = (f64::from(width) - 2.0 * MARGIN) / (max_x - min_x).max(f64::EPSILON);
+	let scale_y = (f64::from(height) - 2.0 * MARGIN) / (max_y - min_y).max(f64::EPSILON);
+	let scale = scale_x.min(scale_y);
+	let height = f64::from(height);
+	move |(x, y)| {
+		(
+			(MARGIN + (x - min_x) * scale).round() as i64,
+			(height - MARGIN - (y - min_y) * scale).round() as i64,
+		)
+	}
+}
+
+/// Draws a line using Bresenham's algorithm.
+fn draw_line(frame: &mut [u8], width: u32, height: u32, from: (i64, i64), to: (i64, i64)) {
+	let (dx, dy) = ((to.0 - from.0).abs(), -(to.1 - from.1).abs());
+	let (step_x, step_y) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+	let (mut x, mut y) = from;
+	let mut error = dx + dy;
+	loop {
+		set_pixel(frame, width, height, (x, y), EDGE);
+		if (x, y) == to {
+			break;
+		}
+		let e2 = 2 * error;
+		if e2 >= dy {
+			error += dy;
+			x += step_x;
+		}
+		if e2 <= dx {
+			error += dx;
+			y += step_y;
+		}
+	}
+}
+
+fn set_pixel(frame: &mut [u8], width: u32, height: u32, (x, y): (i64, i64), colour: [u8; 4]) {
+	if let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) {
+		if x < width && y < height {
+			let idx = (y as usize * width as usize + x as usize) * 4;
+			frame[idx..idx + 4].copy_from_slice(&colour);
+		}
+	}
+}
@@ -0,0 +1,7 @@
+//! Ant colony optimisation for the travelling salesman problem: Ant System,
+//! MAX–MIN Ant System and Ant Colony System, on instances in the TSPLIB
+//! format. See Dorigo and Stützle, "Ant Colony Optimization" (2004).
+
+pub mod colony;
+pub mod draw;
+pub mod tsp;
@@ -0,0 +1,231 @@
+use std::{f64::consts::PI, fs, io, path::Path};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error("missing {0} in TSPLIB file")]
+	Missing(&'static str),
+
+	#[error("invalid line {1} in TSPLIB file: {0:?}")]
+	InvalidLine(String, usize),
+
+	#[error("unsupported {0} {1:?}, only EUC_2D, CEIL_2D, ATT and GEO are supported")]
+	Unsupported(&'static str, String),
+
+	#[error("expected {0} cities, but found {1}")]
+	DimensionMismatch(usize, usize),
+
+	#[error(transparent)]
+	Io(#[from] io::Error),
+}
+
+/// How the distance between two cities is calculated from their coordinates,
+/// as defined by TSPLIB. All of these round to whole numbers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum EdgeWeightType {
+	/// Euclidean distance, rounded to the nearest integer.
+	#[default]
+	Euc2D,
+
+	/// Euclidean distance, rounded up.
+	Ceil2D,
+
+	/// Pseudo-Euclidean distance, used by the `att` instances.
+	Att,
+
+	/// Geographical distance in kilometres, with coordinates given as
+	/// `DDD.MM` (degrees and minutes) latitude and longitude.
+	Geo,
+}
+
+impl EdgeWeightType {
+	/// Returns the distance between two cities.
+	#[must_use]
+	pub fn distance(self, a: (f64, f64), b: (f64, f64)) -> f64 {
+		let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+		match self {
+			EdgeWeightType::Euc2D => dx.hypot(dy).round(),
+			EdgeWeightType::Ceil2D => dx.hypot(dy).ceil(),
+			EdgeWeightType::Att => {
+				let r = ((dx * dx + dy * dy) / 10.0).sqrt();
+				let t = r.round();
+				if t < r {
+					t + 1.0
+				} else {
+					t
+				}
+			}
+			EdgeWeightType::Geo => {
+				const RADIUS: f64 = 6378.388;
+				let (lat_a, lon_a) = (geo_radians(a.0), geo_radians(a.1));
+				let (lat_b, lon_b) = (geo_radians(b.0), geo_radians(b.1));
+				let q1 = (lon_a - lon_b).cos();
+				let q2 = (lat_a - lat_b).cos();
+				let q3 = (lat_a + lat_b).cos();
+				(RADIUS * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0).trunc()
+			}
+		}
+	}
+}
+
+/// Converts a TSPLIB `DDD.MM` coordinate to radians.
+fn geo_radians(x: f64) -> f64 {
+	let degrees = x.trunc();
+	let minutes = x - degrees;
+	PI * (degrees + 5.0 * minutes / 3.0) / 180.0
+}
+
+/// A symmetric travelling salesman problem instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+	pub name: String,
+	pub cities: Vec<(f64, f64)>,
+	pub edge_weight_type: EdgeWeightType,
+
+	/// The distance between every pair of cities, indexed by `a * n + b`.
+	distances: Vec<f64>,
+}
+
+impl Instance {
+	#[must_use]
+	pub fn new(name: String, cities: Vec<(f64, f64)>, edge_weight_type: EdgeWeightType) -> Self {
+		let distances = cities
+			.iter()
+			.flat_map(|&a| cities.iter().map(move |&b| edge_weight_type.distance(a, b)))
+			.collect();
+		Self {
+			name,
+			cities,
+			edge_weight_type,
+			distances,
+		}
+	}
+
+	/// Parses an instance in the TSPLIB format. Only symmetric instances with
+	/// a `NODE_COORD_SECTION` are supported.
+	///
+	/// # Errors
+	/// Returns an error if the instance is not valid, or uses an unsupported
+	/// type or edge weight type.
+	pub fn from_tsplib(text: &str) -> Result<Self, Error> {
+		let mut name = String::new();
+		let mut dimension = None;
+		let mut edge_weight_type = None;
+		let mut cities = Vec::new();
+		let mut in_coords = false;
+
+		for (line_nr, line) in text.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() {
+				continue;
+			}
+			if line == "EOF" {
+				break;
+			}
+			if in_coords {
+				let mut fields = line.split_whitespace().skip(1).map(str::parse::<f64>);
+				if let (Some(Ok(x)), Some(Ok(y))) = (fields.next(), fields.next()) {
+					cities.push((x, y));
+					continue;
+				}
+				if line.starts_with(|c: char| c.is_ascii_digit()) {
+					return Err(Error::InvalidLine(line.to_owned(), line_nr + 1));
+				}
+				// Another section starts
+				in_coords = false;
+			}
+			if line.starts_with("NODE_COORD_SECTION") {
+				in_coords = true;
+				continue;
+			}
+
+			let Some((key, value)) = line.split_once(':') else {
+				continue;
+			};
+			let value = value.trim();
+			match key.trim() {
+				"NAME" => value.clone_into(&mut name),
+				"TYPE" if value != "TSP" => {
+					return Err(Error::Unsupported("TYPE", value.to_owned()))
+				}
+				"DIMENSION" => {
+					let n = value
+						.parse()
+						.map_err(|_| Error::InvalidLine(line.to_owned(), line_nr + 1))?;
+					dimension = Some(n);
+				}
+				"EDGE_WEIGHT_TYPE" => {
+					edge_weight_type = Some(match value {
+						"EUC_2D" => EdgeWeightType::Euc2D,
+						"CEIL_2D" => EdgeWeightType::Ceil2D,
+						"ATT" => EdgeWeightType::Att,
+						"GEO" => EdgeWeightType::Geo,
+						_ => return Err(Error::Unsupported("EDGE_WEIGHT_TYPE", value.to_owned())),
+					});
+				}
+				_ => {}
+			}
+		}
+
+		let dimension = dimension.ok_or(Error::Missing("DIMENSION"))?;
+		let edge_weight_type = edge_weight_type.ok_or(Error::Missing("EDGE_WEIGHT_TYPE"))?;
+		if cities.is_empty() {
+			return Err(Error::Missing("NODE_COORD_SECTION"));
+		}
+		if cities.len() != dimension {
+			return Err(Error::DimensionMismatch(dimension, cities.len()));
+		}
+		Ok(Self::new(name, cities, edge_weight_type))
+	}
+
+	/// Reads an instance from a TSPLIB `.tsp` file.
+	///
+	/// # Errors
+	/// Returns an error if the file cannot be read, or see
+	/// [`Instance::from_tsplib`].
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+		Self::from_tsplib(&fs::read_to_string(path)?)
+	}
+
+	#[must_use]
+	pub fn n_cities(&self) -> usize {
+		self.cities.len()
+	}
+
+	#[inline]
+	#[must_use]
+	pub fn distance(&self, a: usize, b: usize) -> f64 {
+		self.distances[a * self.n_cities() + b]
+	}
+
+	/// Returns the length of the closed tour visiting the cities in order.
+	#[must_use]
+	pub fn tour_length(&self, tour: &[usize]) -> f64 {
+		tour.iter()
+			.zip(tour.iter().cycle().skip(1))
+			.map(|(&a, &b)| self.distance(a, b))
+			.sum()
+	}
+
+	/// Returns the tour that starts at city 0 and always goes to the nearest
+	/// unvisited city.
+	#[must_use]
+	pub fn nearest_neighbour_tour(&self) -> Vec<usize> {
+		let n = self.n_cities();
+		let mut visited = vec![false; n];
+		let mut tour = Vec::with_capacity(n);
+		let mut current = 0;
+		for _ in 0..n {
+			visited[current] = true;
+			tour.push(current);
+			if let Some(next) = (0..n).filter(|&city| !visited[city]).min_by(|&a, &b| {
+				self.distance(current, a)
+					.total_cmp(&self.distance(current, b))
+			}) {
+				current = next;
+			}
+		}
+		tour
+	}
+}
@@ -1,7 +1,16 @@
-use std::path::PathBuf;
-
+use std::{collections::HashMap, path::PathBuf};
+
+use cellular_automata::{
+	cpm::{
+		cell_types::{AdhesionMatrix, PerType},
+		config::{self, Config, TermConfig},
+	},
+	world::Coord,
+};
 use clap::Parser;
 
+use crate::act_cpm::ActCPMCell;
+
 #[derive(Parser)]
 #[command(
 	version,
@@ -29,6 +38,11 @@ pub struct Args {
 	)]
 	pub save_interval: u32,
 
+	/// Record the centroid of every cell after every step, and write them to
+	/// this CSV file on exit.
+	#[arg(long, value_name = "FILE")]
+	pub trajectories: Option<PathBuf>,
+
 	/// Stop after this many simulation steps.
 	#[arg(short, long, value_name = "ITER")]
 	pub iter: Option<u32>,
@@ -41,6 +55,11 @@ pub struct Args {
 	#[arg(long, value_name = "HOR_RES", default_value_t = 5)]
 	pub obstacle_grid: usize,
 
+	/// Load the model from a TOML file instead of from the options below. Cell
+	/// types are 0 (background), 1 (cell) and 2 (obstacle).
+	#[arg(short, long, value_name = "FILE")]
+	pub config: Option<PathBuf>,
+
 	/// Simulation temperature.
 	#[arg(long, default_value_t = 20.0)]
 	pub temp: f32,
@@ -73,7 +92,122 @@ pub struct Args {
 	#[arg(long, default_value_t = 300.0)]
 	pub l_act: f32,
 
-	/// Log frame times.
+	/// Adhesion penalty between cells and obstacles [default: 10 × λ adhesion].
+	#[arg(long)]
+	pub obstacle_adhesion: Option<f32>,
+
+	/// Target volume of obstacles in number of pixels [default: half the
+	/// cell volume].
+	#[arg(long, value_name = "PIXELS")]
+	pub obstacle_volume: Option<u32>,
+
+	/// Target perimeter of obstacles in number of pixel edges.
+	#[arg(long, value_name = "EDGES", default_value_t = 0)]
+	pub obstacle_perimeter: u32,
+
+	/// Prevent cells from breaking into disconnected pieces.
+	#[arg(long, default_value_t = false)]
+	pub connected: bool,
+
+	/// Log frame times and the number of fragmented cells.
 	#[arg(short, long, default_value_t = false)]
 	pub verbose: bool,
 }
+
+impl Args {
+	/// Returns the initial pixel of every obstacle and cell in a `width` x
+	/// `height` world, laid out on the grids. Obstacles get the first IDs.
+	///
+	/// # Errors
+	/// Returns an error if two of them would start on the same pixel (which
+	/// would merge them), or if there are more than fit in an ID.
+	pub fn seeds(&self, width: usize, height: usize) -> Result<Vec<(Coord, ActCPMCell)>, String> {
+		let kind = |cell: ActCPMCell| {
+			if cell.is_obstacle() {
+				"obstacle"
+			} else {
+				"cell"
+			}
+		};
+		let grids = [(self.obstacle_grid, 0, true), (self.cell_grid, 8, false)];
+		let mut seeds = Vec::new();
+		let mut occupied = HashMap::new();
+		for (grid, offset, obstacle) in grids {
+			for x in 0..grid {
+				for y in 0..grid {
+					let idx = (
+						((x * width / grid + offset) % width) as u32,
+						((y * height / grid + offset) % height) as u32,
+					);
+					let id = u32::try_from(seeds.len() + 1)
+						.map_err(|_| "too many cells and obstacles for 32-bit IDs".to_owned())?;
+					let cell = ActCPMCell(id, obstacle);
+					if let Some(other) = occupied.insert(idx, cell) {
+						return Err(format!(
+							"{} {} and {} {id} would both start at {idx:?} in a {width}x{height} \
+							 world; use a different --cell-grid or --obstacle-grid",
+							kind(other),
+							other.0,
+							kind(cell),
+						));
+					}
+					seeds.push((idx, cell));
+				}
+			}
+		}
+		Ok(seeds)
+	}
+
+	/// Returns the configuration of the model, either loaded from
+	/// `--config` or built from the other options.
+	pub fn config(&self) -> Result<Config, config::Error> {
+		if let Some(path) = &self.config {
+			return Config::load(path);
+		}
+
+		let per_type = |cell, obstacle| {
+			let mut values = vec![Default::default(); 3];
+			values[ActCPMCell::CELL] = cell;
+			values[ActCPMCell::OBSTACLE] = obstacle;
+			PerType::Each(values)
+		};
+		let adhesion = self.l_adhesion;
+		let obstacle_adhesion = self.obstacle_adhesion.unwrap_or(10.0 * adhesion);
+		let obstacle_volume = self.obstacle_volume.unwrap_or(self.volume / 2);
+		#[rustfmt::skip]
+		let j = AdhesionMatrix::new(&[
+			vec![0.0, adhesion, adhesion],
+			vec![adhesion, adhesion, obstacle_adhesion],
+			vec![adhesion, obstacle_adhesion, adhesion],
+		])
+		.expect("adhesion matrix is symmetric");
+
+		let mut config = Config {
+			temperature: self.temp,
+			verify: false,
+			verify_interval: None,
+			lifecycle: None,
+			terms: vec![
+				TermConfig::Adhesion { j },
+				TermConfig::Volume {
+					target: per_type(self.volume, obstacle_volume),
+					lambda: self.l_volume.into(),
+				},
+				TermConfig::Perimeter {
+					target: per_type(self.perimeter, self.obstacle_perimeter),
+					lambda: self.l_perimeter.into(),
+				},
+				TermConfig::Act {
+					lambda: self.l_act.into(),
+					max_act: self.max_act.into(),
+				},
+			],
+		};
+		if self.connected {
+			config.terms.push(TermConfig::Connectivity {
+				penalty: f32::INFINITY.into(),
+			});
+		}
+		Ok(config)
+	}
+}
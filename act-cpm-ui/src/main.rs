@@ -6,15 +6,23 @@ mod cli;
 
 use std::{
 	fs::{create_dir, File},
+	io::{self, BufWriter},
 	path::Path,
 	time::Instant,
 };
 
-use act_cpm::{ActCPM, ActCPMCell};
+use act_cpm::ActCPMCell;
 
-use cellular_automata::{cpm::CPM, world::World};
+use cellular_automata::{
+	cpm::{
+		config, connectivity::fragmented_cells, generic::GenericCPM,
+		trajectory::write_trajectories_csv, CPM,
+	},
+	world::World,
+	Cell,
+};
 
-use clap::Parser as _;
+use clap::{error::ErrorKind, CommandFactory as _, Parser as _};
 use cli::Args;
 use pixels::{Pixels, SurfaceTexture};
 use winit::{
@@ -44,21 +52,34 @@ struct Ui<Cpm: CPM<WIDTH, HEIGHT>> {
 fn main() {
 	let args = Args::parse();
 
-	let (mut ui, event_loop) = init(&args);
+	let (mut ui, event_loop) = match init(&args) {
+		Ok(init) => init,
+		Err(err) => {
+			eprintln!("Could not load the configuration: {err}");
+			std::process::exit(1);
+		}
+	};
 
 	let mut modifiers = ModifiersState::default();
 
 	event_loop
-		.run(move |event, window_target| {
+		.run(|event, window_target| {
 			if let Event::WindowEvent { event, .. } = event {
 				handle_window_event(&mut ui, &args, event, window_target, &mut modifiers);
 			}
 		})
 		.unwrap();
+
+	if let Some(path) = &args.trajectories {
+		if let Err(err) = save_trajectories(&ui.model, path) {
+			eprintln!("Could not save the trajectories: {err}");
+			std::process::exit(1);
+		}
+	}
 }
 
-fn handle_window_event<Cpm: CPM<WIDTH, HEIGHT>>(
-	ui: &mut Ui<Cpm>,
+fn handle_window_event(
+	ui: &mut Ui<Model>,
 	args: &Args,
 	event: WindowEvent,
 	window_target: &EventLoopWindowTarget<()>,
@@ -70,8 +91,7 @@ fn handle_window_event<Cpm: CPM<WIDTH, HEIGHT>>(
 			let start_time = Instant::now();
 			if ui.running {
 				for _ in 0..ui.speed {
-					ui.model.step(&mut ui.world);
-					ui.iter += 1;
+					step(ui);
 					if args.output.is_some()
 						&& args.save_interval > 0
 						&& ui.iter % args.save_interval == 0
@@ -85,16 +105,17 @@ fn handle_window_event<Cpm: CPM<WIDTH, HEIGHT>>(
 				ui.window.request_redraw();
 			}
 			let update_time = Instant::now();
-			ui.world.draw(ui.pixels.frame_mut(), WIDTH * SCALE, SCALE);
+			draw(ui);
 			ui.pixels.render().unwrap();
 			let draw_time = Instant::now();
 			if ui.running {
 				if args.verbose {
 					println!(
-						"update:{:3}ms ({:2}ms/i) total:{:3}ms",
+						"update:{:3}ms ({:2}ms/i) total:{:3}ms fragmented:{}",
 						update_time.duration_since(start_time).as_millis(),
 						update_time.duration_since(start_time).as_millis() / u128::from(ui.speed),
-						draw_time.duration_since(start_time).as_millis()
+						draw_time.duration_since(start_time).as_millis(),
+						fragmented_cells(&ui.world).len(),
 					);
 				} else {
 					print!(
@@ -127,8 +148,7 @@ fn handle_window_event<Cpm: CPM<WIDTH, HEIGHT>>(
 			}
 			Key::Named(NamedKey::ArrowRight) if !ui.running => {
 				let start_time = Instant::now();
-				ui.model.step(&mut ui.world);
-				ui.iter += 1;
+				step(ui);
 				let update_time = Instant::now();
 				println!(
 					"update: {:4}us",
@@ -148,8 +168,44 @@ fn handle_window_event<Cpm: CPM<WIDTH, HEIGHT>>(
 	}
 }
 
-fn save_image<Cpm: CPM<WIDTH, HEIGHT>>(ui: &mut Ui<Cpm>, args: &Args) {
-	ui.world.draw(ui.pixels.frame_mut(), WIDTH * SCALE, SCALE);
+/// Performs a single step, and reports the delta energy mismatches and tracker
+/// divergences found by the model (see [`GenericCPM::verify`] and
+/// [`GenericCPM::verify_interval`]).
+fn step(ui: &mut Ui<Model>) {
+	ui.model.step(&mut ui.world);
+	ui.iter += 1;
+	let mismatches = ui.model.take_mismatches();
+	if let Some(mismatch) = mismatches.first() {
+		// Clear current line and put cursor at beginning of line (in case of non-verbose output)
+		eprintln!(
+			"\x1b[1K\rStep {}: {} delta energy mismatches, the first: {mismatch}",
+			ui.iter,
+			mismatches.len()
+		);
+	}
+	if let Some((steps, divergence)) = ui.model.take_divergences().first() {
+		eprintln!("\x1b[1K\rStep {steps}: {divergence}");
+	}
+}
+
+fn draw<Cpm: CPM<WIDTH, HEIGHT, C = ActCPMCell>>(ui: &mut Ui<Cpm>) {
+	ui.world.draw_with(
+		ui.pixels.frame_mut(),
+		WIDTH * SCALE,
+		SCALE,
+		|world, idx, cell| {
+			// Obstacles keep their own colour, even if they are active
+			if cell.is_obstacle() {
+				cell.colour()
+			} else {
+				ui.model.colour(world, idx, cell)
+			}
+		},
+	);
+}
+
+fn save_image<Cpm: CPM<WIDTH, HEIGHT, C = ActCPMCell>>(ui: &mut Ui<Cpm>, args: &Args) {
+	draw(ui);
 	save(
 		&args.output.clone().unwrap_or_default(),
 		&ui.pixels,
@@ -159,22 +215,25 @@ fn save_image<Cpm: CPM<WIDTH, HEIGHT>>(ui: &mut Ui<Cpm>, args: &Args) {
 	println!("\x1b[1K\rSaved image {}.png", ui.iter);
 }
 
-#[must_use]
-fn init(args: &Args) -> (Ui<ActCPM>, EventLoop<()>) {
-	let world = create_world(args);
-
-	#[rustfmt::skip] // to keep the parameters on separate lines
-	let model = ActCPM::new(
-		args.temp,
-		args.l_adhesion,
-		args.volume,
-		args.l_volume,
-		args.perimeter,
-		args.l_perimeter,
-		args.max_act,
-		args.l_act,
-		&world,
-	);
+fn save_trajectories(model: &Model, path: &Path) -> io::Result<()> {
+	let Some(trajectories) = model.trajectories() else {
+		return Ok(());
+	};
+	let mut out = BufWriter::new(File::create(path)?);
+	write_trajectories_csv(&mut out, trajectories.tracks())?;
+	// Clear current line and put cursor at beginning of line (in case of non-verbose output)
+	println!("\x1b[1K\rSaved trajectories to {}", path.display());
+	Ok(())
+}
+
+type Model = GenericCPM<WIDTH, HEIGHT, ActCPMCell>;
+
+fn init(args: &Args) -> Result<(Ui<Model>, EventLoop<()>), config::Error> {
+	let mut world = create_world(args);
+	let mut model = args.config()?.build(&mut world)?;
+	if args.trajectories.is_some() {
+		model.track(&world);
+	}
 
 	let event_loop = EventLoop::new().unwrap();
 	event_loop.set_control_flow(ControlFlow::Wait);
@@ -207,27 +266,19 @@ fn init(args: &Args) -> (Ui<ActCPM>, EventLoop<()>) {
 		iter: 0,
 	};
 
-	(ui, event_loop)
+	Ok((ui, event_loop))
 }
 
 #[must_use]
-fn create_world(args: &Args) -> World<200, 200, ActCPMCell> {
+fn create_world(args: &Args) -> World<WIDTH, HEIGHT, ActCPMCell> {
+	let seeds = args.seeds(WIDTH, HEIGHT).unwrap_or_else(|err| {
+		Args::command()
+			.error(ErrorKind::ValueValidation, err)
+			.exit()
+	});
 	let mut world: World<WIDTH, HEIGHT, _> = World::default();
-	for x in 0..args.obstacle_grid {
-		for y in 0..args.obstacle_grid {
-			world.img[(
-				x * WIDTH / args.obstacle_grid,
-				y * HEIGHT / args.obstacle_grid,
-			)] = ActCPMCell((x * args.obstacle_grid + y + 1) as u8, 80, true);
-		}
-	}
-	for x in 0..args.cell_grid {
-		for y in 0..args.cell_grid {
-			world.img[(
-				x * WIDTH / args.cell_grid + 8,
-				y * HEIGHT / args.cell_grid + 8,
-			)] = ActCPMCell((x * args.cell_grid + y + 1) as u8, 80, false);
-		}
+	for (idx, cell) in seeds {
+		world.img[idx] = cell;
 	}
 	world
 }
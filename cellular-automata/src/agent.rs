@@ -0,0 +1,121 @@
+use crate::{
+	world::{Coord, World},
+	Cell,
+};
+
+/// An agent that lives on top of a lattice [`World`]. Contrary to an
+/// [`Automaton`](crate::ca::Automaton), which updates every cell at once from
+/// its neighbourhood, an agent only reads and writes the cells around its own
+/// position, and can move around.
+pub trait Agent<const W: usize, const H: usize> {
+	type C: Cell;
+
+	/// Returns the current position of this agent.
+	#[must_use]
+	fn position(&self) -> Coord;
+
+	/// Returns the colour with which this agent is drawn on top of the world.
+	#[must_use]
+	fn colour(&self) -> [u8; 4];
+
+	/// Performs a single update of this agent, which may modify the world.
+	fn step(&mut self, world: &mut World<W, H, Self::C>);
+
+	/// Draws this agent on top of a frame previously drawn with
+	/// [`World::draw`].
+	fn draw(&self, frame: &mut [u8], frame_width: usize, scale: usize) {
+		let (x, y) = self.position();
+		let colour = self.colour();
+		let left = x as usize * scale;
+		// Clip the agent to the frame, like `World::draw_with`
+		let right = (left + scale).min(frame_width);
+		for py in y as usize * scale..(y as usize + 1) * scale {
+			let start = (py * frame_width + left) * 4;
+			let end = (py * frame_width + right) * 4;
+			let Some(row) = frame.get_mut(start..end) else {
+				break;
+			};
+			for pixel in row.chunks_exact_mut(4) {
+				pixel.copy_from_slice(&colour);
+			}
+		}
+	}
+}
+
+/// Steps every agent once, in order. Later agents see the changes made by
+/// earlier agents.
+pub fn step_all<const W: usize, const H: usize, A: Agent<W, H>>(
+	agents: &mut [A],
+	world: &mut World<W, H, A::C>,
+) {
+	for agent in agents {
+		agent.step(world);
+	}
+}
+
+/// Draws every agent on top of a frame previously drawn with [`World::draw`].
+pub fn draw_all<const W: usize, const H: usize, A: Agent<W, H>>(
+	agents: &[A],
+	frame: &mut [u8],
+	frame_width: usize,
+	scale: usize,
+) {
+	for agent in agents {
+		agent.draw(frame, frame_width, scale);
+	}
+}
+
+/// One of the four directions on the lattice.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub enum Direction {
+	#[default]
+	North,
+	East,
+	South,
+	West,
+}
+
+/// A relative turn, as used by lattice agents.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub enum Turn {
+	#[default]
+	None,
+	Right,
+	UTurn,
+	Left,
+}
+
+impl Direction {
+	pub const ALL: [Direction; 4] = [
+		Direction::North,
+		Direction::East,
+		Direction::South,
+		Direction::West,
+	];
+
+	/// Returns the direction after making the given `turn`.
+	#[must_use]
+	pub fn turn(self, turn: Turn) -> Self {
+		Self::ALL[(self as usize + turn as usize) % 4]
+	}
+
+	/// Returns the `(dx, dy)` offset of a single step in this direction. North
+	/// points towards the top of the world (decreasing y).
+	#[must_use]
+	pub fn offset(self) -> (i32, i32) {
+		match self {
+			Direction::North => (0, -1),
+			Direction::East => (1, 0),
+			Direction::South => (0, 1),
+			Direction::West => (-1, 0),
+		}
+	}
+
+	/// Returns the coordinate one step in this direction from `idx`, wrapping
+	/// around the edges of the world.
+	#[must_use]
+	pub fn step_from<const W: usize, const H: usize>(self, idx: Coord) -> Coord {
+		// The wrapping does not depend on the cell type
+		World::<W, H, bool>::get_neighbour_idx(idx, self.offset())
+	}
+}
@@ -0,0 +1,173 @@
+use core::hash::{Hash, Hasher};
+use std::collections::{hash_map::DefaultHasher, HashMap};
+
+use crate::{
+	ca::Automaton,
+	world::{Coord, World},
+	Cell,
+};
+
+/// The long-term behaviour of a run, as detected by a [`RunAnalyser`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+	/// The world stopped changing after `transient` steps.
+	FixedPoint { transient: usize },
+
+	/// After `transient` steps, the world repeats itself every `period` steps.
+	Cycle { transient: usize, period: usize },
+
+	/// After `transient` steps, the pattern repeats itself every `period`
+	/// steps, but moved by `displacement` cells. Only detected when using
+	/// translation-invariant hashing.
+	Spaceship {
+		transient: usize,
+		period: usize,
+		displacement: (i64, i64),
+	},
+}
+
+/// Detects when a run reaches a fixed point or enters a cycle, by hashing the
+/// state of the world after every step. Only hashes are stored, so a hash
+/// collision could (very rarely) report a cycle that is not there.
+#[derive(Default)]
+pub struct RunAnalyser {
+	translation_invariant: bool,
+
+	/// Maps each state hash to the step at which it was first seen, and the
+	/// offset of the pattern at that step.
+	seen: HashMap<u64, (usize, Coord)>,
+	step: usize,
+}
+
+impl RunAnalyser {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Creates a [`RunAnalyser`] that ignores the position of the pattern, so
+	/// that moving patterns (spaceships) are detected as well. The pattern is
+	/// taken to be the bounding box of all non-default cells, so patterns that
+	/// wrap around the edges of the world are not recognised as the same.
+	#[must_use]
+	pub fn translation_invariant() -> Self {
+		Self {
+			translation_invariant: true,
+			..Self::default()
+		}
+	}
+
+	/// Returns the number of states observed so far.
+	#[must_use]
+	pub fn steps(&self) -> usize {
+		self.step
+	}
+
+	/// Records the current state of the world. Call this once for the initial
+	/// state and then after every step. Returns the outcome as soon as a state
+	/// is seen for the second time.
+	pub fn observe<const W: usize, const H: usize, C: Cell + Hash + Default>(
+		&mut self,
+		world: &World<W, H, C>,
+	) -> Option<Outcome> {
+		let (hash, offset) = if self.translation_invariant {
+			hash_pattern(world)
+		} else {
+			(hash_world(world), (0, 0))
+		};
+
+		let step = self.step;
+		self.step += 1;
+
+		let (first_step, first_offset) = *self.seen.entry(hash).or_insert((step, offset));
+		if first_step == step {
+			return None;
+		}
+
+		let transient = first_step;
+		let period = step - first_step;
+		let displacement = (
+			i64::from(offset.0) - i64::from(first_offset.0),
+			i64::from(offset.1) - i64::from(first_offset.1),
+		);
+		Some(if displacement != (0, 0) {
+			Outcome::Spaceship {
+				transient,
+				period,
+				displacement,
+			}
+		} else if period == 1 {
+			Outcome::FixedPoint { transient }
+		} else {
+			Outcome::Cycle { transient, period }
+		})
+	}
+}
+
+/// Runs `automaton` on `world` until it reaches a fixed point or cycle, or
+/// until `max_steps` steps have passed (in which case [`None`] is returned).
+pub fn analyse<const W: usize, const H: usize, A>(
+	automaton: &A,
+	world: &mut World<W, H, A::C>,
+	mut analyser: RunAnalyser,
+	max_steps: usize,
+) -> Option<Outcome>
+where
+	A: Automaton<W, H>,
+	A::C: Hash + Default,
+{
+	if let Some(outcome) = analyser.observe(world) {
+		return Some(outcome);
+	}
+	for _ in 0..max_steps {
+		automaton.step(world);
+		if let Some(outcome) = analyser.observe(world) {
+			return Some(outcome);
+		}
+	}
+	None
+}
+
+/// Returns the hash of every cell in the interior of the world (see
+/// [`World::interior_rows`]).
+#[must_use]
+fn hash_world<const W: usize, const H: usize, C: Cell + Hash>(world: &World<W, H, C>) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	for row in world.interior_rows() {
+		row.hash(&mut hasher);
+	}
+	hasher.finish()
+}
+
+/// Returns the hash of the bounding box around all non-default cells in the
+/// interior of the world, and the position of that bounding box.
+#[must_use]
+fn hash_pattern<const W: usize, const H: usize, C: Cell + Hash + Default>(
+	world: &World<W, H, C>,
+) -> (u64, Coord) {
+	let background = C::default();
+	let (mut min_x, mut min_y, mut max_x, mut max_y) = (W, H, 0, 0);
+	for (y, row) in world.interior_rows().enumerate() {
+		for (x, cell) in row.iter().enumerate() {
+			if *cell != background {
+				min_x = min_x.min(x);
+				min_y = min_y.min(y);
+				max_x = max_x.max(x);
+				max_y = max_y.max(y);
+			}
+		}
+	}
+
+	let mut hasher = DefaultHasher::new();
+	if min_x > max_x {
+		// Empty world
+		return (hasher.finish(), (0, 0));
+	}
+
+	(max_x - min_x, max_y - min_y).hash(&mut hasher);
+	for row in world.interior_rows().skip(min_y).take(max_y - min_y + 1) {
+		row[min_x..=max_x].hash(&mut hasher);
+	}
+	// Offset by the outermost row and column
+	(hasher.finish(), (min_x as u32 + 1, min_y as u32 + 1))
+}
@@ -0,0 +1,312 @@
+//! Ant foraging on a lattice: ants leave the nest, wander until they find food
+//! and carry it back. Searching ants mark the way home and returning ants mark
+//! the way to the food, each with their own pheromone, so that the colony
+//! converges on short trails between the nest and food sources.
+
+use rand::{distributions::WeightedIndex, prelude::*, rngs::StdRng};
+
+use crate::{
+	agent::{self, Agent, Direction, Turn},
+	layer::{Layer, LayerId},
+	pde::diffusion::{Boundary, Diffusion, Scheme},
+	world::{Coord, World},
+	Cell,
+};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub enum Terrain {
+	#[default]
+	Empty,
+	Nest,
+
+	/// A food source with the given number of units left.
+	Food(u8),
+
+	/// Ants cannot walk through walls.
+	Wall,
+}
+
+impl Cell for Terrain {
+	fn colour(&self) -> [u8; 4] {
+		match self {
+			Terrain::Empty => [0x00, 0x00, 0x00, 0xff],
+			Terrain::Nest => [0x99, 0x66, 0x33, 0xff],
+			Terrain::Food(_) => [0x33, 0xcc, 0x33, 0xff],
+			Terrain::Wall => [0x88, 0x88, 0x88, 0xff],
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+	/// The amount of pheromone an ant deposits right after leaving the nest or
+	/// a food source.
+	pub deposit: f32,
+
+	/// The factor by which the deposit decreases with every step away from the
+	/// nest or food source, so that trails are stronger close to their goal.
+	pub trail_decay: f32,
+
+	/// The fraction of pheromone that evaporates every step.
+	pub evaporation: f32,
+
+	/// The diffusion coefficient of the pheromones.
+	pub diffusion: f32,
+
+	/// The attractiveness of a cell without pheromone, relative to the
+	/// pheromone concentration. Larger values make ants explore more.
+	pub threshold: f32,
+
+	/// The exponent of the choice function `(threshold + pheromone)^exponent`.
+	/// Larger values make ants follow trails more strictly.
+	pub exponent: f32,
+}
+
+impl Default for Params {
+	fn default() -> Self {
+		Self {
+			deposit: 1.0,
+			trail_decay: 0.98,
+			evaporation: 0.01,
+			diffusion: 0.05,
+			threshold: 0.01,
+			exponent: 2.0,
+		}
+	}
+}
+
+/// The pheromone layers of a colony.
+#[derive(Debug, Clone, Copy)]
+pub struct Pheromones {
+	/// Deposited by ants carrying food, followed by searching ants.
+	pub to_food: LayerId<f32>,
+
+	/// Deposited by searching ants, followed by ants carrying food.
+	pub to_nest: LayerId<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Ant {
+	pub pos: Coord,
+	pub dir: Direction,
+	pub carrying: bool,
+
+	/// The number of food units this ant has brought back to the nest.
+	pub delivered: u32,
+
+	/// The amount of pheromone this ant deposits on its next step.
+	strength: f32,
+	params: Params,
+	pheromones: Pheromones,
+	rng: StdRng,
+}
+
+impl Ant {
+	#[must_use]
+	pub fn new(
+		pos: Coord,
+		dir: Direction,
+		params: Params,
+		pheromones: Pheromones,
+		seed: u64,
+	) -> Self {
+		Self {
+			pos,
+			dir,
+			carrying: false,
+			delivered: 0,
+			strength: params.deposit,
+			params,
+			pheromones,
+			rng: StdRng::seed_from_u64(seed),
+		}
+	}
+
+	/// Turns towards one of the three cells in front (forward, left or right),
+	/// chosen with probability proportional to `(threshold + pheromone)^exponent`
+	/// of the pheromone that leads to the current goal. Turns around if all
+	/// three are walls.
+	fn choose_direction<const W: usize, const H: usize>(&mut self, world: &World<W, H, Terrain>) {
+		let trail = world.layers.get(if self.carrying {
+			self.pheromones.to_nest
+		} else {
+			self.pheromones.to_food
+		});
+		let options: Vec<(Direction, f32)> = [Turn::None, Turn::Left, Turn::Right]
+			.into_iter()
+			.map(|turn| self.dir.turn(turn))
+			.filter_map(|dir| {
+				let target = dir.step_from::<W, H>(self.pos);
+				(world.img[target] != Terrain::Wall).then(|| {
+					let weight =
+						(self.params.threshold + trail.get(target)).powf(self.params.exponent);
+					(dir, weight)
+				})
+			})
+			.collect();
+
+		self.dir = match WeightedIndex::new(options.iter().map(|(_, weight)| weight)) {
+			Ok(dist) => options[dist.sample(&mut self.rng)].0,
+			Err(_) if options.is_empty() => self.dir.turn(Turn::UTurn),
+			Err(_) => options[self.rng.gen_range(0..options.len())].0,
+		};
+	}
+}
+
+impl<const W: usize, const H: usize> Agent<W, H> for Ant {
+	type C = Terrain;
+
+	fn position(&self) -> Coord {
+		self.pos
+	}
+
+	fn colour(&self) -> [u8; 4] {
+		if self.carrying {
+			[0xff, 0xff, 0x33, 0xff]
+		} else {
+			[0xff, 0xff, 0xff, 0xff]
+		}
+	}
+
+	/// Picks up or drops food, deposits pheromone, and moves one cell.
+	fn step(&mut self, world: &mut World<W, H, Terrain>) {
+		match world.img[self.pos] {
+			Terrain::Food(amount) if !self.carrying => {
+				world.img[self.pos] = if amount > 1 {
+					Terrain::Food(amount - 1)
+				} else {
+					Terrain::Empty
+				};
+				self.carrying = true;
+				self.strength = self.params.deposit;
+				self.dir = self.dir.turn(Turn::UTurn);
+			}
+			Terrain::Nest => {
+				if self.carrying {
+					self.carrying = false;
+					self.delivered += 1;
+					self.dir = self.dir.turn(Turn::UTurn);
+				}
+				self.strength = self.params.deposit;
+			}
+			_ => {}
+		}
+
+		let deposit_layer = if self.carrying {
+			self.pheromones.to_food
+		} else {
+			self.pheromones.to_nest
+		};
+		let layer = world.layers.get_mut(deposit_layer);
+		layer.set(self.pos, layer.get(self.pos).max(self.strength));
+		self.strength *= self.params.trail_decay;
+
+		self.choose_direction(world);
+		let target = self.dir.step_from::<W, H>(self.pos);
+		if world.img[target] != Terrain::Wall {
+			self.pos = target;
+		}
+	}
+}
+
+/// A colony of ants sharing a nest and two pheromone layers.
+pub struct Colony {
+	pub ants: Vec<Ant>,
+	pub pheromones: Pheromones,
+	diffusion: Diffusion,
+}
+
+impl Colony {
+	/// Creates a colony of `n_ants` ants starting at `nest` in random
+	/// directions, marks `nest` as [`Terrain::Nest`], and adds the pheromone
+	/// layers to `world`.
+	#[must_use]
+	pub fn new<const W: usize, const H: usize>(
+		world: &mut World<W, H, Terrain>,
+		nest: Coord,
+		n_ants: usize,
+		params: Params,
+		seed: u64,
+	) -> Self {
+		world.img[nest] = Terrain::Nest;
+		let pheromones = Pheromones {
+			to_food: world.layers.add(Layer::new(0.0)),
+			to_nest: world.layers.add(Layer::new(0.0)),
+		};
+		let mut rng = StdRng::seed_from_u64(seed);
+		let ants = (0..n_ants)
+			.map(|_| {
+				let dir = *Direction::ALL.choose(&mut rng).unwrap_or(&Direction::North);
+				Ant::new(nest, dir, params, pheromones, rng.gen())
+			})
+			.collect();
+		Self {
+			ants,
+			pheromones,
+			diffusion: Diffusion::new(
+				params.diffusion,
+				params.evaporation,
+				Scheme::Explicit,
+				Boundary::Periodic,
+			),
+		}
+	}
+
+	/// Returns the number of food units brought back to the nest so far.
+	#[must_use]
+	pub fn delivered(&self) -> u32 {
+		self.ants.iter().map(|ant| ant.delivered).sum()
+	}
+
+	/// Steps every ant once, then lets the pheromones evaporate and diffuse.
+	pub fn step<const W: usize, const H: usize>(&mut self, world: &mut World<W, H, Terrain>) {
+		agent::step_all(&mut self.ants, world);
+		for id in [self.pheromones.to_food, self.pheromones.to_nest] {
+			self.diffusion.step(world.layers.get_mut(id), 1.0);
+		}
+	}
+
+	/// Draws the world with the trail strength of empty cells shown in red
+	/// (towards food) and blue (towards the nest), and the ants on top.
+	#[allow(clippy::cast_sign_loss)]
+	pub fn draw<const W: usize, const H: usize>(
+		&self,
+		world: &World<W, H, Terrain>,
+		frame: &mut [u8],
+		frame_width: usize,
+		scale: usize,
+	) {
+		let to_food = world.layers.get(self.pheromones.to_food);
+		let to_nest = world.layers.get(self.pheromones.to_nest);
+		world.draw_with(frame, frame_width, scale, |_, idx, cell| {
+			if cell == Terrain::Empty {
+				let intensity = |value: f32| (value.sqrt().min(1.0) * 255.0) as u8;
+				[
+					intensity(to_food.get(idx)),
+					0x00,
+					intensity(to_nest.get(idx)),
+					0xff,
+				]
+			} else {
+				cell.colour()
+			}
+		});
+		agent::draw_all::<W, H, _>(&self.ants, frame, frame_width, scale);
+	}
+}
+
+/// Places a square food source of `size`×`size` cells with its top-left
+/// corner at `corner`, with `amount` units of food per cell.
+pub fn place_food<const W: usize, const H: usize>(
+	world: &mut World<W, H, Terrain>,
+	corner: Coord,
+	size: u32,
+	amount: u8,
+) {
+	for dy in 0..size {
+		for dx in 0..size {
+			let idx = ((corner.0 + dx) % W as u32, (corner.1 + dy) % H as u32);
+			world.img[idx] = Terrain::Food(amount);
+		}
+	}
+}
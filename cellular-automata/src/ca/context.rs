@@ -0,0 +1,84 @@
+use rand::RngCore;
+
+use crate::layer::{LayerId, Layers};
+
+/// Everything an [`Automaton`](crate::ca::Automaton) rule can look at when
+/// using [`rule_with_context`](crate::ca::Automaton::rule_with_context): the
+/// neighbourhood, the position of the cell, the current step, a random number
+/// generator for this cell, and read access to auxiliary layers.
+pub struct Context<'a, const W: usize, const H: usize, C> {
+	pub neighbourhood: [C; 9],
+	pub x: usize,
+	pub y: usize,
+
+	/// The number of steps taken before this one.
+	pub step: u64,
+
+	/// A random number generator that only depends on the seed, the step and
+	/// the position of the cell. See [`CellRng`].
+	pub rng: CellRng,
+
+	pub layers: &'a Layers<W, H>,
+}
+
+impl<const W: usize, const H: usize, C: Copy> Context<'_, W, H, C> {
+	/// Returns the current state of the cell itself.
+	#[inline]
+	#[must_use]
+	pub fn cell(&self) -> C {
+		self.neighbourhood[4]
+	}
+
+	/// Returns the value of layer `id` at the position of the cell.
+	#[inline]
+	#[must_use]
+	pub fn layer<T: Copy + 'static>(&self, id: LayerId<T>) -> T {
+		self.layers.get(id).img[(self.x, self.y)]
+	}
+}
+
+/// A small and fast random number generator (`SplitMix64`) that is seeded per
+/// cell and per step. Because the random numbers a cell receives do not depend
+/// on the order in which cells are updated or on what other cells do, two runs
+/// with the same seed draw exactly the same random numbers for the same cell.
+///
+/// Not suitable for cryptographic purposes.
+#[derive(Clone, Debug)]
+pub struct CellRng(u64);
+
+impl CellRng {
+	#[must_use]
+	pub fn new(seed: u64, step: u64, x: usize, y: usize) -> Self {
+		let mut rng = Self(seed);
+		for value in [step, x as u64, y as u64] {
+			rng = Self(rng.next_u64() ^ value);
+		}
+		rng
+	}
+}
+
+impl RngCore for CellRng {
+	fn next_u32(&mut self) -> u32 {
+		(self.next_u64() >> 32) as u32
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^ (z >> 31)
+	}
+
+	fn fill_bytes(&mut self, dest: &mut [u8]) {
+		for chunk in dest.chunks_mut(8) {
+			let bytes = self.next_u64().to_le_bytes();
+			chunk.copy_from_slice(&bytes[..chunk.len()]);
+		}
+	}
+
+	fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+		self.fill_bytes(dest);
+		Ok(())
+	}
+}
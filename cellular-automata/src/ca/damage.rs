@@ -0,0 +1,192 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{ca::Automaton, world::World, Cell};
+
+/// Above this fraction of differing cells at the end of a run, the damage is
+/// considered to have spread through the whole world.
+pub const SPREAD_THRESHOLD: f32 = 0.05;
+
+/// Wolfram's qualitative classes of automaton behaviour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WolframClass {
+	/// Evolves to a homogeneous state, any damage dies out.
+	Homogeneous,
+
+	/// Evolves to separated stable or periodic structures, damage stays local.
+	Periodic,
+
+	/// Chaotic, damage spreads through the whole world.
+	Chaotic,
+
+	/// Complex localised structures, damage spreads slowly and irregularly.
+	Complex,
+}
+
+/// The Hamming distance between two copies of a world over time, one of which
+/// was perturbed at the start.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DamageRun {
+	/// The number of differing cells, before the first step and after every
+	/// step.
+	pub distances: Vec<u32>,
+
+	/// The number of cells in the interior of the world, where the damage is
+	/// measured.
+	pub cells: usize,
+
+	/// Whether the unperturbed world ended up with all interior cells equal.
+	pub homogeneous: bool,
+}
+
+impl DamageRun {
+	/// Returns the fraction of differing cells over time.
+	#[must_use]
+	pub fn normalised(&self) -> Vec<f32> {
+		self.distances
+			.iter()
+			.map(|&d| d as f32 / self.cells as f32)
+			.collect()
+	}
+
+	/// Returns the mean fraction of differing cells over the last quarter of the
+	/// run.
+	#[must_use]
+	pub fn final_damage(&self) -> f32 {
+		let tail = &self.distances[self.distances.len() * 3 / 4..];
+		tail.iter().map(|&d| d as f32).sum::<f32>() / tail.len().max(1) as f32 / self.cells as f32
+	}
+
+	/// Returns a Lyapunov-style exponent: the mean exponential growth rate of
+	/// the damage, `ln(d(t) / d(0)) / t`, measured up to the point where the
+	/// damage stops growing (or dies out). Positive values indicate that small
+	/// perturbations grow, negative values that they shrink. Returns [`None`]
+	/// if there was no initial damage or no step was taken.
+	#[must_use]
+	pub fn lyapunov_exponent(&self) -> Option<f32> {
+		let &initial = self.distances.first()?;
+		if initial == 0 || self.distances.len() < 2 {
+			return None;
+		}
+
+		let peak = self
+			.distances
+			.iter()
+			.enumerate()
+			.max_by_key(|&(_, &d)| d)
+			.map_or(0, |(t, _)| t);
+		let (t, d) = match self.distances.iter().position(|&d| d == 0) {
+			// Damage died out: measure the decay to the last non-zero step,
+			// since ln(0) is undefined
+			Some(t) => (t - 1, self.distances[t - 1]),
+			None if peak > 0 => (peak, self.distances[peak]),
+			None => (self.distances.len() - 1, *self.distances.last()?),
+		};
+		if t == 0 {
+			// Died out in the first step
+			return Some(f32::NEG_INFINITY);
+		}
+		Some((d as f32 / initial as f32).ln() / t as f32)
+	}
+
+	/// Classifies the behaviour of the automaton from the damage over time.
+	/// This is a heuristic: damage that dies out indicates class I or II
+	/// (depending on whether the world became homogeneous), damage that stays
+	/// around its initial size indicates class II, and damage that grows
+	/// indicates class III or IV (depending on whether it spread through more
+	/// than [`SPREAD_THRESHOLD`] of the world).
+	#[must_use]
+	pub fn classify(&self) -> WolframClass {
+		let initial = self.distances.first().map_or(0.0, |&d| d as f32) / self.cells as f32;
+		let last = self.final_damage();
+		if last == 0.0 {
+			if self.homogeneous {
+				WolframClass::Homogeneous
+			} else {
+				WolframClass::Periodic
+			}
+		} else if last <= initial * 2.0 {
+			WolframClass::Periodic
+		} else if last >= SPREAD_THRESHOLD {
+			WolframClass::Chaotic
+		} else {
+			WolframClass::Complex
+		}
+	}
+}
+
+/// Runs two copies of `world` for `steps` steps, one of which has `n_flips`
+/// random cells in the interior (see [`World::interior_rows`]) changed by
+/// `perturb` at the start, and records the Hamming distance between them. Both copies use the same per-cell random numbers
+/// (see [`Automaton::step_with_context`]), so for stochastic rules any
+/// difference is caused by the perturbation alone.
+pub fn damage_spreading<const W: usize, const H: usize, A, F>(
+	automaton: &A,
+	world: &World<W, H, A::C>,
+	n_flips: usize,
+	steps: usize,
+	seed: u64,
+	mut perturb: F,
+) -> DamageRun
+where
+	A: Automaton<W, H>,
+	F: FnMut(A::C, &mut StdRng) -> A::C,
+{
+	let mut rng = StdRng::seed_from_u64(seed);
+	let mut original = world.clone();
+	let mut perturbed = world.clone();
+	for _ in 0..n_flips {
+		let idx = (rng.gen_range(1..W - 1), rng.gen_range(1..H - 1));
+		perturbed.img[idx] = perturb(perturbed.img[idx], &mut rng);
+	}
+
+	let step_seed = rng.gen();
+	let mut distances = Vec::with_capacity(steps + 1);
+	distances.push(hamming_distance(&original, &perturbed));
+	for step in 0..steps as u64 {
+		automaton.step_with_context(&mut original, step, step_seed);
+		automaton.step_with_context(&mut perturbed, step, step_seed);
+		distances.push(hamming_distance(&original, &perturbed));
+	}
+
+	let mut cells = original.interior_rows().flatten();
+	let first = cells.next();
+	let homogeneous = cells.all(|cell| Some(cell) == first);
+	DamageRun {
+		distances,
+		cells: (W - 2) * (H - 2),
+		homogeneous,
+	}
+}
+
+/// Returns the number of cells in the interior that differ between `a` and
+/// `b`. The outer ring is left out, as it only holds copies of the interior.
+#[must_use]
+pub fn hamming_distance<const W: usize, const H: usize, C: Cell>(
+	a: &World<W, H, C>,
+	b: &World<W, H, C>,
+) -> u32 {
+	a.interior_rows()
+		.flatten()
+		.zip(b.interior_rows().flatten())
+		.filter(|(a, b)| a != b)
+		.count() as u32
+}
+
+/// Returns the mean fraction of differing cells at every step, over several
+/// runs of the same length.
+#[must_use]
+pub fn mean_damage(runs: &[DamageRun]) -> Vec<f32> {
+	let len = runs
+		.iter()
+		.map(|run| run.distances.len())
+		.min()
+		.unwrap_or(0);
+	(0..len)
+		.map(|t| {
+			runs.iter()
+				.map(|run| run.distances[t] as f32 / run.cells as f32)
+				.sum::<f32>()
+				/ runs.len() as f32
+		})
+		.collect()
+}
@@ -1,14 +1,30 @@
-use crate::{ca::Automaton, count_neighbours};
+use rand::Rng;
+
+use crate::{
+	ca::{context::Context, Automaton},
+	count_neighbours,
+};
 
 #[derive(Default)]
 pub struct Grow;
 
+impl Grow {
+	#[must_use]
+	fn next<R: Rng>(neighbourhood: [bool; 9], rng: &mut R) -> bool {
+		let cell = neighbourhood[4];
+		let n_neighbours = count_neighbours(neighbourhood, |cell| cell);
+		cell || rng.gen::<f32>() < f32::from(n_neighbours) * 0.1
+	}
+}
+
 impl<const W: usize, const H: usize> Automaton<W, H> for Grow {
 	type C = bool;
 
 	fn rule(&self, neighbourhood: [bool; 9]) -> bool {
-		let cell = neighbourhood[4];
-		let n_neighbours = count_neighbours(neighbourhood, |cell| cell);
-		cell || rand::random::<f32>() < f32::from(n_neighbours) * 0.1
+		Self::next(neighbourhood, &mut rand::thread_rng())
+	}
+
+	fn rule_with_context(&self, ctx: &mut Context<'_, W, H, bool>) -> bool {
+		Self::next(ctx.neighbourhood, &mut ctx.rng)
 	}
 }
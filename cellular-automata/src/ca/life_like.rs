@@ -0,0 +1,86 @@
+use core::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{ca::Automaton, count_neighbours};
+
+/// A Life-like automaton, described by the numbers of live neighbours for
+/// which a dead cell is born and a live cell survives. Conway's Game of Life
+/// is `B3/S23`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct LifeLike {
+	pub birth: [bool; 9],
+	pub survive: [bool; 9],
+}
+
+impl LifeLike {
+	/// Returns the rule of Conway's Game of Life, `B3/S23`.
+	#[must_use]
+	pub fn game_of_life() -> Self {
+		let mut rule = Self::default();
+		rule.birth[3] = true;
+		rule.survive[2] = true;
+		rule.survive[3] = true;
+		rule
+	}
+
+	/// Returns whether a cell with the given state and number of live
+	/// neighbours is alive in the next step.
+	#[inline]
+	#[must_use]
+	pub fn next(&self, cell: bool, n_neighbours: usize) -> bool {
+		if cell {
+			self.survive[n_neighbours]
+		} else {
+			self.birth[n_neighbours]
+		}
+	}
+}
+
+impl<const W: usize, const H: usize> Automaton<W, H> for LifeLike {
+	type C = bool;
+
+	fn rule(&self, neighbourhood: [bool; 9]) -> bool {
+		let n_neighbours = count_neighbours(neighbourhood, |cell| cell);
+		self.next(neighbourhood[4], n_neighbours as usize)
+	}
+}
+
+impl fmt::Display for LifeLike {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "B")?;
+		for n in (0..9).filter(|&n| self.birth[n]) {
+			write!(f, "{n}")?;
+		}
+		write!(f, "/S")?;
+		for n in (0..9).filter(|&n| self.survive[n]) {
+			write!(f, "{n}")?;
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid Life-like rule {0:?}, expected for example B3/S23")]
+pub struct ParseRuleError(pub String);
+
+impl FromStr for LifeLike {
+	type Err = ParseRuleError;
+
+	/// Parses a rule in `B3/S23` notation (case-insensitive).
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let err = || ParseRuleError(s.to_owned());
+		let (birth, survive) = s.split_once('/').ok_or_else(err)?;
+		let birth = birth.strip_prefix(['B', 'b']).ok_or_else(err)?;
+		let survive = survive.strip_prefix(['S', 's']).ok_or_else(err)?;
+
+		let mut rule = Self::default();
+		for (digits, set) in [(birth, &mut rule.birth), (survive, &mut rule.survive)] {
+			for c in digits.chars() {
+				let n = c.to_digit(10).filter(|&n| n <= 8).ok_or_else(err)?;
+				set[n as usize] = true;
+			}
+		}
+		Ok(rule)
+	}
+}
@@ -1,19 +1,61 @@
+pub mod analyser;
+pub mod ants;
+pub mod context;
+pub mod damage;
 pub mod game_of_life;
 pub mod grow;
+pub mod life_like;
+pub mod rule_1d;
+pub mod sandpile;
 pub mod sir;
+pub mod turmite;
+pub mod wireworld;
 
 use crate::{world::World, Cell};
 
+use self::context::{CellRng, Context};
+
 pub trait Automaton<const W: usize, const H: usize> {
 	type C: Cell;
 
 	#[must_use]
 	fn rule(&self, neighbourhood: [Self::C; 9]) -> Self::C;
 
+	/// Like [`Automaton::rule`], but with access to the position of the cell,
+	/// the current step, a per-cell random number generator and auxiliary
+	/// layers. Only used by [`Automaton::step_with_context`]. By default, this
+	/// ignores the context and calls [`Automaton::rule`].
+	#[must_use]
+	fn rule_with_context(&self, ctx: &mut Context<'_, W, H, Self::C>) -> Self::C {
+		self.rule(ctx.neighbourhood)
+	}
+
 	fn step(&self, world: &mut World<W, H, Self::C>)
 	where
 		Self: Sized,
 	{
 		world.convolve(|n| self.rule(n));
 	}
+
+	/// Performs a single step using [`Automaton::rule_with_context`]. `step`
+	/// is the number of steps taken before this one, and `seed` seeds the
+	/// per-cell random number generators (see [`CellRng`]). The rule can read
+	/// the layers of the world through [`Context::layer`].
+	fn step_with_context(&self, world: &mut World<W, H, Self::C>, step: u64, seed: u64)
+	where
+		Self: Sized,
+	{
+		let layers = core::mem::take(&mut world.layers);
+		world.convolve_indexed(|x, y, neighbourhood| {
+			self.rule_with_context(&mut Context {
+				neighbourhood,
+				x,
+				y,
+				step,
+				rng: CellRng::new(seed, step, x, y),
+				layers: &layers,
+			})
+		});
+		world.layers = layers;
+	}
 }
@@ -0,0 +1,127 @@
+use crate::{ca::Automaton, world::World};
+
+/// A one-dimensional binary automaton with neighbourhood radius `radius`,
+/// given by its full rule table. Every row of the [`World`] is treated as a
+/// separate ring of cells, so a world of height `H` runs `H` independent
+/// lattices at once.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Rule1D {
+	radius: usize,
+
+	/// The next state for each of the `2^(2 * radius + 1)` neighbourhoods. The
+	/// leftmost cell of the neighbourhood is the most significant bit of the
+	/// index, as in Wolfram's numbering.
+	table: Box<[bool]>,
+}
+
+impl Rule1D {
+	/// Creates a rule from its table, which should have `2^(2 * radius + 1)`
+	/// entries.
+	///
+	/// # Panics
+	/// Panics if the table does not have the right length.
+	#[must_use]
+	pub fn new(radius: usize, table: Vec<bool>) -> Self {
+		assert_eq!(
+			table.len(),
+			Self::table_len(radius),
+			"rule table for radius {radius} has the wrong length"
+		);
+		Self {
+			radius,
+			table: table.into_boxed_slice(),
+		}
+	}
+
+	/// Creates an elementary (radius 1) automaton from its Wolfram code, for
+	/// example rule 110.
+	#[must_use]
+	pub fn elementary(code: u8) -> Self {
+		Self::new(1, (0..8).map(|i| code & (1 << i) != 0).collect())
+	}
+
+	/// Returns the length of the rule table for a neighbourhood of `radius`.
+	#[must_use]
+	pub fn table_len(radius: usize) -> usize {
+		1 << (2 * radius + 1)
+	}
+
+	#[must_use]
+	pub fn radius(&self) -> usize {
+		self.radius
+	}
+
+	#[must_use]
+	pub fn table(&self) -> &[bool] {
+		&self.table
+	}
+
+	#[must_use]
+	pub fn table_mut(&mut self) -> &mut [bool] {
+		&mut self.table
+	}
+
+	/// Returns the next state of a single row, wrapping around at the edges.
+	/// The neighbourhood may be wider than the row, in which case it wraps
+	/// around more than once.
+	#[must_use]
+	pub fn step_row(&self, row: &[bool]) -> Vec<bool> {
+		let len = row.len();
+		if len == 0 {
+			return Vec::new();
+		}
+		let window = 2 * self.radius + 1;
+		let mask = (1 << window) - 1;
+
+		// Start with the neighbourhood of the cell left of the first cell, and
+		// shift in one cell at a time. Reduce the radius first, so that going
+		// left of the first cell cannot underflow
+		let left = self.radius % len;
+		let mut idx = 0;
+		for i in 0..window - 1 {
+			idx = (idx << 1) | usize::from(row[(i + len - left) % len]);
+		}
+		(0..len)
+			.map(|x| {
+				idx = ((idx << 1) | usize::from(row[(x + self.radius) % len])) & mask;
+				self.table[idx]
+			})
+			.collect()
+	}
+}
+
+impl<const W: usize, const H: usize> Automaton<W, H> for Rule1D {
+	type C = bool;
+
+	/// Looks up the middle row of the neighbourhood. A 3×3 neighbourhood only
+	/// contains the nearest neighbours, so for a radius larger than 1 the cells
+	/// further away are taken to be equal to the nearest neighbours. Note that
+	/// [`Automaton::step`] does not use this, see [`Rule1D::step_row`].
+	fn rule(&self, neighbourhood: [bool; 9]) -> bool {
+		let [left, centre, right] = [neighbourhood[3], neighbourhood[4], neighbourhood[5]];
+		let mut idx = 0;
+		for i in 0..=2 * self.radius {
+			let cell = match i.cmp(&self.radius) {
+				core::cmp::Ordering::Less => left,
+				core::cmp::Ordering::Equal => centre,
+				core::cmp::Ordering::Greater => right,
+			};
+			idx = (idx << 1) | usize::from(cell);
+		}
+		self.table[idx]
+	}
+
+	fn step(&self, world: &mut World<W, H, bool>) {
+		for row in world.img.rows_mut() {
+			let next = self.step_row(row);
+			row.copy_from_slice(&next);
+		}
+	}
+
+	/// The rule is deterministic and does not use the context, so this is the
+	/// same as [`Automaton::step`] (which, unlike the default, runs the full
+	/// neighbourhood of [`Rule1D::step_row`]).
+	fn step_with_context(&self, world: &mut World<W, H, bool>, _step: u64, _seed: u64) {
+		Automaton::<W, H>::step(self, world);
+	}
+}
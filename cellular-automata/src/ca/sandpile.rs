@@ -0,0 +1,265 @@
+use std::io::{self, Write};
+
+use rand::Rng;
+
+use crate::{
+	world::{Coord, World},
+	Cell,
+};
+
+/// The number of grains at which a site topples, one grain to each of its four
+/// von Neumann neighbours.
+pub const THRESHOLD: u32 = 4;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct Grains(pub u32);
+
+impl Cell for Grains {
+	fn colour(&self) -> [u8; 4] {
+		match self.0 {
+			0 => [0x00, 0x00, 0x00, 0xff],
+			1 => [0x33, 0x33, 0x99, 0xff],
+			2 => [0x66, 0x99, 0xcc, 0xff],
+			3 => [0xff, 0xcc, 0x33, 0xff],
+			_ => [0xff, 0xff, 0xff, 0xff],
+		}
+	}
+}
+
+/// Statistics of a single avalanche, caused by dropping one grain.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Avalanche {
+	/// The total number of topplings.
+	pub size: u32,
+
+	/// The number of parallel toppling waves until the pile was stable again.
+	pub duration: u32,
+
+	/// The number of distinct sites that toppled at least once.
+	pub area: u32,
+}
+
+/// One bin of a logarithmically binned histogram. The bin covers the values
+/// in `lower..upper`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bin {
+	pub lower: u32,
+	pub upper: u32,
+	pub count: u32,
+
+	/// The count normalised by the bin width and the total number of samples,
+	/// such that a power law shows up as a straight line on a log-log plot.
+	pub density: f64,
+}
+
+/// The Bak–Tang–Wiesenfeld sandpile. Unlike the automata implementing
+/// [`Automaton`](crate::ca::Automaton), a single step (dropping a grain) keeps
+/// toppling sites until the whole pile is stable again. Grains that topple
+/// over the edge of the [`World`] are lost, so the world does not wrap.
+#[derive(Default)]
+pub struct Sandpile {
+	avalanches: Vec<Avalanche>,
+}
+
+impl Sandpile {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the statistics of every avalanche so far, one for each dropped
+	/// grain (including drops that did not cause any toppling).
+	#[must_use]
+	pub fn avalanches(&self) -> &[Avalanche] {
+		&self.avalanches
+	}
+
+	/// Drops a single grain at a random site and relaxes the pile.
+	pub fn step<const W: usize, const H: usize, R: Rng>(
+		&mut self,
+		world: &mut World<W, H, Grains>,
+		rng: &mut R,
+	) -> Avalanche {
+		let idx = (rng.gen_range(0..W as u32), rng.gen_range(0..H as u32));
+		self.drop_grain(world, idx)
+	}
+
+	/// Drops a single grain at `idx`, relaxes the pile and records the
+	/// resulting avalanche.
+	pub fn drop_grain<const W: usize, const H: usize>(
+		&mut self,
+		world: &mut World<W, H, Grains>,
+		idx: Coord,
+	) -> Avalanche {
+		world.img[idx].0 += 1;
+		let avalanche = relax(world, vec![idx]);
+		self.avalanches.push(avalanche);
+		avalanche
+	}
+
+	/// Returns the log-binned histogram of avalanche sizes. See
+	/// [`log_histogram`].
+	#[must_use]
+	pub fn size_histogram(&self, bins_per_decade: u32) -> Vec<Bin> {
+		log_histogram(self.avalanches.iter().map(|a| a.size), bins_per_decade)
+	}
+
+	/// Returns the log-binned histogram of avalanche durations. See
+	/// [`log_histogram`].
+	#[must_use]
+	pub fn duration_histogram(&self, bins_per_decade: u32) -> Vec<Bin> {
+		log_histogram(self.avalanches.iter().map(|a| a.duration), bins_per_decade)
+	}
+
+	/// Returns the log-binned histogram of avalanche areas. See
+	/// [`log_histogram`].
+	#[must_use]
+	pub fn area_histogram(&self, bins_per_decade: u32) -> Vec<Bin> {
+		log_histogram(self.avalanches.iter().map(|a| a.area), bins_per_decade)
+	}
+}
+
+/// Topples all unstable sites in the world until it is stable, and returns the
+/// statistics of the resulting avalanche. `unstable` should contain every site
+/// that may be unstable at the start.
+///
+/// Because the sandpile is abelian, the order of the topplings within a wave
+/// does not influence the final state.
+pub fn relax<const W: usize, const H: usize>(
+	world: &mut World<W, H, Grains>,
+	mut unstable: Vec<Coord>,
+) -> Avalanche {
+	let mut avalanche = Avalanche::default();
+	let mut toppled = vec![false; W * H];
+	let mut queued = vec![false; W * H];
+
+	loop {
+		// Sites may have been queued and toppled again within the same wave
+		unstable.retain(|&idx| world.img[idx].0 >= THRESHOLD);
+		if unstable.is_empty() {
+			break;
+		}
+		avalanche.duration += 1;
+		let mut next = Vec::new();
+
+		for &idx in &unstable {
+			queued[idx.1 as usize * W + idx.0 as usize] = false;
+		}
+		for idx in unstable {
+			let site = &mut world.img[idx];
+			let n_topples = site.0 / THRESHOLD;
+			site.0 %= THRESHOLD;
+			avalanche.size += n_topples;
+
+			let flat_idx = idx.1 as usize * W + idx.0 as usize;
+			if !toppled[flat_idx] {
+				toppled[flat_idx] = true;
+				avalanche.area += 1;
+			}
+
+			for neighbour in open_neighbours_idx::<W, H>(idx).into_iter().flatten() {
+				world.img[neighbour].0 += n_topples;
+				let flat_neighbour = neighbour.1 as usize * W + neighbour.0 as usize;
+				if world.img[neighbour].0 >= THRESHOLD && !queued[flat_neighbour] {
+					queued[flat_neighbour] = true;
+					next.push(neighbour);
+				}
+			}
+		}
+
+		unstable = next;
+	}
+
+	avalanche
+}
+
+/// Returns the von Neumann neighbours of `idx` that lie within the world, or
+/// [`None`] for neighbours beyond the edge.
+#[must_use]
+fn open_neighbours_idx<const W: usize, const H: usize>(idx: Coord) -> [Option<Coord>; 4] {
+	let (x, y) = idx;
+	[
+		x.checked_sub(1).map(|x| (x, y)),
+		Some((x + 1, y)).filter(|&(x, _)| x < W as u32),
+		y.checked_sub(1).map(|y| (x, y)),
+		Some((x, y + 1)).filter(|&(_, y)| y < H as u32),
+	]
+}
+
+/// Bins `values` into logarithmically spaced bins, with `bins_per_decade` bins
+/// for every factor of 10. Zeroes (for example drops that did not cause an
+/// avalanche) cannot be placed on a logarithmic scale and are skipped. Bins
+/// that would be narrower than a single integer are merged with the next bin.
+#[must_use]
+pub fn log_histogram<I: Iterator<Item = u32>>(values: I, bins_per_decade: u32) -> Vec<Bin> {
+	let values: Vec<u32> = values.filter(|&v| v > 0).collect();
+	let Some(&max) = values.iter().max() else {
+		return Vec::new();
+	};
+
+	let factor = 10f64.powf(1.0 / f64::from(bins_per_decade.max(1)));
+	let mut edges = vec![1u32];
+	let mut last = 1;
+	let mut edge = 1.0;
+	while last <= max {
+		edge *= factor;
+		#[allow(clippy::cast_sign_loss)]
+		let next = edge.ceil() as u32;
+		if next > last {
+			edges.push(next);
+			last = next;
+		}
+	}
+
+	let mut counts = vec![0; edges.len() - 1];
+	for value in &values {
+		// `edges` is sorted, so the bin is the number of edges <= value, minus one
+		let bin = edges.partition_point(|&edge| edge <= *value) - 1;
+		counts[bin] += 1;
+	}
+
+	let total = values.len() as f64;
+	edges
+		.windows(2)
+		.zip(counts)
+		.map(|(edge, count)| Bin {
+			lower: edge[0],
+			upper: edge[1],
+			count,
+			density: f64::from(count) / f64::from(edge[1] - edge[0]) / total,
+		})
+		.collect()
+}
+
+/// Writes a histogram as CSV, with the columns `lower,upper,count,density`.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn write_histogram_csv<Wr: Write>(out: &mut Wr, histogram: &[Bin]) -> io::Result<()> {
+	writeln!(out, "lower,upper,count,density")?;
+	for bin in histogram {
+		writeln!(
+			out,
+			"{},{},{},{}",
+			bin.lower, bin.upper, bin.count, bin.density
+		)?;
+	}
+	Ok(())
+}
+
+/// Writes the statistics of every avalanche as CSV, with the columns
+/// `size,duration,area`.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn write_avalanches_csv<Wr: Write>(out: &mut Wr, avalanches: &[Avalanche]) -> io::Result<()> {
+	writeln!(out, "size,duration,area")?;
+	for avalanche in avalanches {
+		writeln!(
+			out,
+			"{},{},{}",
+			avalanche.size, avalanche.duration, avalanche.area
+		)?;
+	}
+	Ok(())
+}
@@ -1,6 +1,11 @@
-use crate::{ca::Automaton, count_neighbours, Cell};
+use rand::Rng;
 
-#[derive(Clone, Copy, Default, PartialEq, Eq)]
+use crate::{
+	ca::{context::Context, Automaton},
+	count_neighbours, Cell,
+};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum State {
 	#[default]
 	Susceptible,
@@ -28,25 +33,21 @@ impl Sir {
 	pub fn new(p_cure: f32) -> Self {
 		Self { p_cure }
 	}
-}
-
-impl<const W: usize, const H: usize> Automaton<W, H> for Sir {
-	type C = State;
 
-	fn rule(&self, neighbourhood: [State; 9]) -> State {
-		let cell = neighbourhood[4];
+	/// Returns the next state of a cell with `n_infected` infected neighbours.
+	/// Every infected neighbour adds 0.1 to the probability of infection.
+	#[must_use]
+	pub(crate) fn next<R: Rng>(&self, cell: State, n_infected: usize, rng: &mut R) -> State {
 		match cell {
 			State::Susceptible => {
-				let n_inf_neighbours =
-					count_neighbours(neighbourhood, |cell| cell == State::Infected);
-				if rand::random::<f32>() < f32::from(n_inf_neighbours) * 0.1 {
+				if rng.gen::<f32>() < n_infected as f32 * 0.1 {
 					State::Infected
 				} else {
 					State::Susceptible
 				}
 			}
 			State::Infected => {
-				if rand::random::<f32>() < self.p_cure {
+				if rng.gen::<f32>() < self.p_cure {
 					State::Resistant
 				} else {
 					State::Infected
@@ -55,4 +56,22 @@ impl<const W: usize, const H: usize> Automaton<W, H> for Sir {
 			State::Resistant => State::Resistant,
 		}
 	}
+
+	#[must_use]
+	fn next_lattice<R: Rng>(&self, neighbourhood: [State; 9], rng: &mut R) -> State {
+		let n_infected = count_neighbours(neighbourhood, |cell| cell == State::Infected);
+		self.next(neighbourhood[4], n_infected.into(), rng)
+	}
+}
+
+impl<const W: usize, const H: usize> Automaton<W, H> for Sir {
+	type C = State;
+
+	fn rule(&self, neighbourhood: [State; 9]) -> State {
+		self.next_lattice(neighbourhood, &mut rand::thread_rng())
+	}
+
+	fn rule_with_context(&self, ctx: &mut Context<'_, W, H, State>) -> State {
+		self.next_lattice(ctx.neighbourhood, &mut ctx.rng)
+	}
 }
@@ -0,0 +1,182 @@
+use crate::{
+	agent::{Agent, Direction, Turn},
+	world::{Coord, World},
+	Cell,
+};
+
+/// The colours used to draw the states of a [`Colour`] cell, cycling when
+/// there are more states than colours.
+const PALETTE: [[u8; 4]; 8] = [
+	[0xff, 0xff, 0xff, 0xff],
+	[0x00, 0x00, 0x00, 0xff],
+	[0xcc, 0x33, 0x33, 0xff],
+	[0x33, 0x99, 0x33, 0xff],
+	[0x33, 0x66, 0xcc, 0xff],
+	[0xff, 0xcc, 0x33, 0xff],
+	[0x99, 0x33, 0xcc, 0xff],
+	[0x33, 0xcc, 0xcc, 0xff],
+];
+
+/// A cell with one of several colours, which a [`Turmite`] reads and writes.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct Colour(pub u8);
+
+impl Cell for Colour {
+	fn colour(&self) -> [u8; 4] {
+		PALETTE[self.0 as usize % PALETTE.len()]
+	}
+}
+
+/// A single entry in the transition table of a [`Turmite`].
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct Transition {
+	/// The colour to write to the current cell.
+	pub write: u8,
+
+	/// The turn to make before moving one step forward.
+	pub turn: Turn,
+
+	/// The internal state of the turmite after this transition.
+	pub next_state: u8,
+}
+
+/// A turmite: an agent with an internal state, that on every step looks up the
+/// colour of the cell it stands on in its transition table, writes a new
+/// colour, turns and moves one cell forward.
+#[derive(Clone, Debug)]
+pub struct Turmite {
+	pub pos: Coord,
+	pub dir: Direction,
+	pub state: u8,
+	n_colours: u8,
+
+	/// The transitions, indexed by `state * n_colours + colour`.
+	table: Box<[Transition]>,
+}
+
+impl Turmite {
+	/// Creates a new [`Turmite`] from its transition table, which contains
+	/// `n_colours` entries for every state.
+	///
+	/// # Panics
+	/// Panics if the table is empty, its length is not a multiple of
+	/// `n_colours`, or a transition writes a colour or goes to a state that is
+	/// not in the table.
+	#[must_use]
+	pub fn new(pos: Coord, dir: Direction, n_colours: u8, table: Vec<Transition>) -> Self {
+		assert!(
+			n_colours > 0 && !table.is_empty() && table.len().is_multiple_of(n_colours as usize),
+			"transition table needs n_colours ({n_colours}) entries for every state"
+		);
+		let n_states = table.len() / n_colours as usize;
+		for (i, transition) in table.iter().enumerate() {
+			assert!(
+				transition.write < n_colours,
+				"transition {i} writes colour {}, but there are only {n_colours} colours",
+				transition.write
+			);
+			assert!(
+				(transition.next_state as usize) < n_states,
+				"transition {i} goes to state {}, but there are only {n_states} states",
+				transition.next_state
+			);
+		}
+		Self {
+			pos,
+			dir,
+			state: 0,
+			n_colours,
+			table: table.into_boxed_slice(),
+		}
+	}
+
+	/// Creates Langton's ant: turn right on a white cell, left on a black cell,
+	/// and flip the colour of the cell.
+	#[must_use]
+	pub fn langtons_ant(pos: Coord) -> Self {
+		Self::from_turns(pos, &[Turn::Right, Turn::Left])
+	}
+
+	/// Creates a single-state turmite (a generalised ant) that makes the `i`th
+	/// turn on a cell with colour `i`, and increments the colour of the cell.
+	/// For example, `[Right, Left]` is Langton's ant.
+	///
+	/// # Panics
+	/// Panics if `turns` is empty or contains more than 255 turns.
+	#[must_use]
+	pub fn from_turns(pos: Coord, turns: &[Turn]) -> Self {
+		let n_colours = u8::try_from(turns.len()).expect("at most 255 colours are supported");
+		let table = turns
+			.iter()
+			.enumerate()
+			.map(|(colour, &turn)| Transition {
+				write: ((colour + 1) % turns.len()) as u8,
+				turn,
+				next_state: 0,
+			})
+			.collect();
+		Self::new(pos, Direction::North, n_colours, table)
+	}
+
+	/// Parses a generalised ant from a string of turns like `"RLR"`, where `N`,
+	/// `R`, `U` and `L` stand for no turn, right, U-turn and left. Returns
+	/// [`None`] if the string is empty or contains other characters.
+	#[must_use]
+	pub fn parse_turns(pos: Coord, turns: &str) -> Option<Self> {
+		let turns = turns
+			.chars()
+			.map(|c| match c.to_ascii_uppercase() {
+				'N' => Some(Turn::None),
+				'R' => Some(Turn::Right),
+				'U' => Some(Turn::UTurn),
+				'L' => Some(Turn::Left),
+				_ => None,
+			})
+			.collect::<Option<Vec<Turn>>>()?;
+		if turns.is_empty() || turns.len() > 255 {
+			return None;
+		}
+		Some(Self::from_turns(pos, &turns))
+	}
+
+	/// Returns the number of colours this turmite distinguishes.
+	#[must_use]
+	pub fn n_colours(&self) -> u8 {
+		self.n_colours
+	}
+
+	/// Returns the transition for the given colour in the current state.
+	///
+	/// # Panics
+	/// Panics if the turmite does not distinguish `colour`.
+	#[must_use]
+	fn transition(&self, colour: Colour) -> Transition {
+		assert!(
+			colour.0 < self.n_colours,
+			"turmite with {} colours is on a cell with colour {}",
+			self.n_colours,
+			colour.0
+		);
+		self.table[self.state as usize * self.n_colours as usize + colour.0 as usize]
+	}
+}
+
+impl<const W: usize, const H: usize> Agent<W, H> for Turmite {
+	type C = Colour;
+
+	fn position(&self) -> Coord {
+		self.pos
+	}
+
+	fn colour(&self) -> [u8; 4] {
+		[0xff, 0x00, 0xff, 0xff]
+	}
+
+	fn step(&mut self, world: &mut World<W, H, Colour>) {
+		let transition = self.transition(world.get_cell(self.pos));
+		world.img[self.pos] = Colour(transition.write);
+		self.state = transition.next_state;
+		self.dir = self.dir.turn(transition.turn);
+		self.pos = self.dir.step_from::<W, H>(self.pos);
+	}
+}
@@ -0,0 +1,75 @@
+use crate::{ca::Automaton, count_neighbours, Cell};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub enum Wire {
+	#[default]
+	Empty,
+	Head,
+	Tail,
+	Conductor,
+}
+
+impl Cell for Wire {
+	fn colour(&self) -> [u8; 4] {
+		match self {
+			Wire::Empty => [0x00, 0x00, 0x00, 0xff],
+			Wire::Head => [0x33, 0x66, 0xff, 0xff],
+			Wire::Tail => [0xff, 0x33, 0x33, 0xff],
+			Wire::Conductor => [0xff, 0xcc, 0x00, 0xff],
+		}
+	}
+}
+
+impl Wire {
+	/// Parses a single character of a text circuit: `.` or space for empty,
+	/// `#` for a conductor, `H` for an electron head and `t` for an electron
+	/// tail. Use with [`World::from_text`](crate::world::World::from_text).
+	#[must_use]
+	pub fn from_char(c: char) -> Option<Self> {
+		match c {
+			'.' | ' ' => Some(Wire::Empty),
+			'#' => Some(Wire::Conductor),
+			'H' => Some(Wire::Head),
+			't' => Some(Wire::Tail),
+			_ => None,
+		}
+	}
+
+	/// Parses the colour of a single pixel of an image circuit, using the same
+	/// colours as [`Cell::colour`]. Fully transparent pixels are empty. Use
+	/// with [`World::from_png`](crate::world::World::from_png).
+	#[must_use]
+	pub fn from_colour(colour: [u8; 4]) -> Option<Self> {
+		if colour[3] == 0 {
+			return Some(Wire::Empty);
+		}
+		[Wire::Empty, Wire::Head, Wire::Tail, Wire::Conductor]
+			.into_iter()
+			.find(|wire| wire.colour() == colour)
+	}
+}
+
+/// Wireworld: electrons (a head followed by a tail) travel along conductors,
+/// which is enough to build wires, diodes and logic gates.
+#[derive(Default)]
+pub struct Wireworld;
+
+impl<const W: usize, const H: usize> Automaton<W, H> for Wireworld {
+	type C = Wire;
+
+	fn rule(&self, neighbourhood: [Wire; 9]) -> Wire {
+		match neighbourhood[4] {
+			Wire::Empty => Wire::Empty,
+			Wire::Head => Wire::Tail,
+			Wire::Tail => Wire::Conductor,
+			Wire::Conductor => {
+				let n_heads = count_neighbours(neighbourhood, |cell| cell == Wire::Head);
+				if n_heads == 1 || n_heads == 2 {
+					Wire::Head
+				} else {
+					Wire::Conductor
+				}
+			}
+		}
+	}
+}
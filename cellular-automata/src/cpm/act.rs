@@ -1,36 +1,22 @@
 use crate::{
 	cpm::CPM,
+	layer::LayerId,
 	world::{Coord, World},
+	Cell,
 };
 
-#[allow(clippy::module_name_repetitions)]
-pub trait ActCell {
+pub trait Act<const W: usize, const H: usize>: CPM<W, H> {
 	#[must_use]
-	fn get_activity(&self) -> u8;
-}
+	fn get_act_penalty(&self, activity_delta: f32) -> f32;
 
-pub trait Act<const W: usize, const H: usize>: CPM<W, H>
-where
-	Self::C: ActCell,
-{
+	/// Returns the layer of the world that holds the activity of every pixel.
 	#[must_use]
-	fn get_act_penalty(&self, activity_delta: f32) -> f32;
+	fn act_layer(&self) -> LayerId<u8>;
 
 	/// Returns the geometric mean of the activity in the neighbourhood of a cell.
 	#[must_use]
 	fn gm_act(&self, world: &World<W, H, Self::C>, idx: Coord) -> f32 {
-		let cell = world.get_cell(idx);
-		world
-			.get_neighbours(idx)
-			.iter()
-			.filter_map(|&neigh| {
-				if neigh == cell {
-					Some(f32::from(neigh.get_activity()))
-				} else {
-					None
-				}
-			})
-			.geometric_mean()
+		gm_act(world, self.act_layer(), idx)
 	}
 
 	/// Returns the delta act energy for copying the cell at `src_idx` into
@@ -48,6 +34,28 @@ where
 	}
 }
 
+/// Returns the geometric mean of the activity in layer `act_layer` of the
+/// neighbours of `idx` that belong to the same cell.
+#[must_use]
+pub fn gm_act<const W: usize, const H: usize, C: Cell>(
+	world: &World<W, H, C>,
+	act_layer: LayerId<u8>,
+	idx: Coord,
+) -> f32 {
+	let cell = world.get_cell(idx);
+	let activity = world.layers.get(act_layer);
+	World::<W, H, C>::get_neighbours_idx(idx)
+		.iter()
+		.filter_map(|&neigh_idx| {
+			if world.get_cell(neigh_idx) == cell {
+				Some(f32::from(activity.get(neigh_idx)))
+			} else {
+				None
+			}
+		})
+		.geometric_mean()
+}
+
 trait GeometricMean {
 	#[must_use]
 	fn geometric_mean(self) -> f32;
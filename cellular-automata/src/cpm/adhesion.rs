@@ -1,5 +1,5 @@
 use crate::{
-	cpm::CPM,
+	cpm::{CPMCell, CPM},
 	world::{Coord, World},
 };
 
@@ -10,12 +10,7 @@ pub trait Adhesion<const W: usize, const H: usize>: CPM<W, H> {
 	/// Returns the adhesion energy for a single cell.
 	#[must_use]
 	fn adhesion(&self, world: &World<W, H, Self::C>, idx: Coord, cell: Self::C) -> f32 {
-		world
-			.get_neighbours(idx)
-			.iter()
-			.filter(|&neigh| *neigh != cell)
-			.map(|&neigh| self.get_adhesion_penalty(cell, neigh))
-			.sum()
+		adhesion(world, idx, cell, |a, b| self.get_adhesion_penalty(a, b))
 	}
 
 	/// Returns the delta adhesion energy for copying the cell at `src_idx` into
@@ -31,4 +26,49 @@ pub trait Adhesion<const W: usize, const H: usize>: CPM<W, H> {
 	) -> f32 {
 		self.adhesion(world, dest_idx, src) - self.adhesion(world, dest_idx, dest)
 	}
+
+	/// Returns the total adhesion energy of `world`.
+	#[must_use]
+	fn adhesion_energy(&self, world: &World<W, H, Self::C>) -> f64 {
+		adhesion_energy(world, |a, b| self.get_adhesion_penalty(a, b))
+	}
+}
+
+/// Returns the adhesion energy of the pixel at `idx` if it had state `cell`:
+/// the sum of `penalty(cell, neighbour)` over all neighbours of a different
+/// cell.
+pub fn adhesion<const W: usize, const H: usize, C: CPMCell, F>(
+	world: &World<W, H, C>,
+	idx: Coord,
+	cell: C,
+	penalty: F,
+) -> f32
+where
+	F: Fn(C, C) -> f32,
+{
+	world
+		.get_neighbours(idx)
+		.iter()
+		.filter(|&neigh| *neigh != cell)
+		.map(|&neigh| penalty(cell, neigh))
+		.sum()
+}
+
+/// Returns the total adhesion energy of `world`: the sum of `penalty` over
+/// all pairs of neighbouring pixels of different cells. `penalty` must be
+/// symmetric.
+pub fn adhesion_energy<const W: usize, const H: usize, C: CPMCell, F>(
+	world: &World<W, H, C>,
+	penalty: F,
+) -> f64
+where
+	F: Fn(C, C) -> f32,
+{
+	// Every pair is counted from both sides
+	world
+		.positions(|_| true)
+		.into_iter()
+		.map(|idx| f64::from(adhesion(world, idx, world.get_cell(idx), &penalty)))
+		.sum::<f64>()
+		/ 2.0
 }
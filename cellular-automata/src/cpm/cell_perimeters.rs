@@ -1,32 +1,22 @@
 use crate::{
-	count_neighbours,
-	cpm::CPMCell,
+	cpm::{
+		registry::{Divergence, Registry},
+		CPMCell,
+	},
 	world::{Coord, World},
 };
 
-use loop9::loop9_img;
-
-pub struct CellPerimeters(Box<[u32]>);
+/// The perimeter of every (non-background) cell: the number of its pixels'
+/// Moore neighbours that belong to another cell, wrapping around the edges of
+/// the world.
+pub struct CellPerimeters(Registry<u32>);
 
 impl CellPerimeters {
 	#[must_use]
 	pub fn from_world<const W: usize, const H: usize, C: CPMCell>(world: &World<W, H, C>) -> Self {
-		let mut perimeters = vec![0; C::MAX_ID + 1].into_boxed_slice();
-
-		// TODO: wrap borders
-
-		loop9_img(world.img.as_ref(), |_x, _y, top, mid, bot| {
-			#[rustfmt::skip]
-			let neighbourhood = [
-				top.prev, top.curr, top.next,
-				mid.prev, mid.curr, mid.next,
-				bot.prev, bot.curr, bot.next,
-			];
-			perimeters[mid.curr.id()] +=
-				u32::from(count_neighbours(neighbourhood, |n| n != mid.curr));
-		});
-
-		Self(perimeters)
+		let mut perimeters = Self(Registry::new());
+		perimeters.recalculate(world);
+		perimeters
 	}
 
 	// Ported from https://github.com/ingewortel/artistoo/blob/master/src/hamiltonian/PerimeterConstraint.js
@@ -44,15 +34,16 @@ impl CellPerimeters {
 			n_new += u32::from(neighbour != src);
 			n_old += u32::from(neighbour != dest);
 			if !neighbour.is_bg() {
-				self.0[neighbour.id()] += u32::from(neighbour == dest);
-				self.0[neighbour.id()] -= u32::from(neighbour == src);
+				let perimeter = self.0.get_mut(neighbour.id());
+				*perimeter += u32::from(neighbour == dest);
+				*perimeter -= u32::from(neighbour == src);
 			}
 		}
 		if !dest.is_bg() {
-			self.0[dest.id()] -= n_old;
+			*self.0.get_mut(dest.id()) -= n_old;
 		}
 		if !src.is_bg() {
-			self.0[src.id()] += n_new;
+			*self.0.get_mut(src.id()) += n_new;
 		}
 	}
 
@@ -60,20 +51,31 @@ impl CellPerimeters {
 		&mut self,
 		world: &World<W, H, C>,
 	) {
-		self.0 = vec![0; C::MAX_ID + 1].into_boxed_slice();
-		loop9_img(world.img.as_ref(), |_x, _y, top, mid, bot| {
-			#[rustfmt::skip]
-			let neighbourhood = [
-				top.prev, top.curr, top.next,
-				mid.prev, mid.curr, mid.next,
-				bot.prev, bot.curr, bot.next,
-			];
-			self.0[mid.curr.id()] += u32::from(count_neighbours(neighbourhood, |n| n != mid.curr));
-		});
+		self.0.clear();
+		for idx in world.positions(|c| !c.is_bg()) {
+			let cell = world.get_cell(idx);
+			let n_other = world
+				.get_neighbours(idx)
+				.iter()
+				.filter(|&&n| n != cell)
+				.count();
+			*self.0.get_mut(cell.id()) += n_other as u32;
+		}
+	}
+
+	/// Compares the incrementally updated perimeters with those of `world`.
+	///
+	/// # Errors
+	/// Returns the first cell whose perimeter differs.
+	pub fn verify<const W: usize, const H: usize, C: CPMCell>(
+		&self,
+		world: &World<W, H, C>,
+	) -> Result<(), Divergence> {
+		Divergence::check("perimeter", &self.0, &Self::from_world(world).0)
 	}
 
 	#[inline]
 	pub fn get<C: CPMCell>(&self, cell: C) -> u32 {
-		self.0[cell.id()]
+		self.0.get(cell.id())
 	}
 }
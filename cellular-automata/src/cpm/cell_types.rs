@@ -0,0 +1,155 @@
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+	#[error("adhesion matrix is empty")]
+	Empty,
+
+	#[error("adhesion matrix row {0} has {1} values, expected {2}")]
+	NotSquare(usize, usize, usize),
+
+	#[error("adhesion matrix is not symmetric at ({0}, {1})")]
+	NotSymmetric(usize, usize),
+}
+
+/// A parameter of a term that is either the same for every cell type, or
+/// given per type (indexed by [`CPMCell::cell_type`](super::CPMCell::cell_type),
+/// so the background comes first). A list must have a value for every type,
+/// which [`Config::build`](super::config::Config::build) checks.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum PerType<T> {
+	All(T),
+	Each(Vec<T>),
+}
+
+impl<T: Copy> PerType<T> {
+	/// Returns the value for `cell_type`, which must be within the list if
+	/// the values are given per type.
+	#[inline]
+	#[must_use]
+	pub fn get(&self, cell_type: usize) -> T {
+		match self {
+			PerType::All(value) => *value,
+			PerType::Each(values) => values[cell_type],
+		}
+	}
+}
+
+impl<T> PerType<T> {
+	/// Returns the number of types with a value, or [`None`] if the value is
+	/// the same for all types.
+	#[must_use]
+	pub fn n_types(&self) -> Option<usize> {
+		match self {
+			PerType::All(_) => None,
+			PerType::Each(values) => Some(values.len()),
+		}
+	}
+}
+
+impl<T: Default> Default for PerType<T> {
+	fn default() -> Self {
+		PerType::All(T::default())
+	}
+}
+
+impl<T> From<T> for PerType<T> {
+	fn from(value: T) -> Self {
+		PerType::All(value)
+	}
+}
+
+/// The symmetric matrix `J` of adhesion penalties between cell types, with a
+/// row for every type.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "RawAdhesionMatrix")]
+pub struct AdhesionMatrix {
+	/// The penalty for every pair of types, or a single value for all pairs.
+	values: Vec<f32>,
+	n_types: usize,
+}
+
+impl AdhesionMatrix {
+	/// Creates a matrix with the same `penalty` between all types.
+	#[must_use]
+	pub fn uniform(penalty: f32) -> Self {
+		Self {
+			values: vec![penalty],
+			n_types: 0,
+		}
+	}
+
+	/// Creates a matrix from its rows.
+	///
+	/// # Errors
+	/// Returns an error if the matrix is empty, not square or not symmetric.
+	pub fn new(rows: &[Vec<f32>]) -> Result<Self, Error> {
+		let n_types = rows.len();
+		if n_types == 0 {
+			return Err(Error::Empty);
+		}
+		for (a, row) in rows.iter().enumerate() {
+			if row.len() != n_types {
+				return Err(Error::NotSquare(a, row.len(), n_types));
+			}
+			for b in 0..a {
+				// The matrix is written out by hand, so it must be exactly symmetric
+				#[allow(clippy::float_cmp)]
+				if row[b] != rows[b][a] {
+					return Err(Error::NotSymmetric(a, b));
+				}
+			}
+		}
+		Ok(Self {
+			values: rows.concat(),
+			n_types,
+		})
+	}
+
+	/// Returns the number of rows, or [`None`] if the penalty is the same
+	/// for all pairs of types.
+	#[must_use]
+	pub fn n_types(&self) -> Option<usize> {
+		(self.n_types > 0).then_some(self.n_types)
+	}
+
+	/// Returns the penalty between types `a` and `b`.
+	///
+	/// # Panics
+	/// Panics if the matrix is not uniform and either type is outside it.
+	#[inline]
+	#[must_use]
+	pub fn get(&self, a: usize, b: usize) -> f32 {
+		if self.n_types == 0 {
+			self.values[0]
+		} else {
+			assert!(
+				a < self.n_types && b < self.n_types,
+				"cell types ({a}, {b}) are outside the adhesion matrix"
+			);
+			self.values[a * self.n_types + b]
+		}
+	}
+}
+
+/// An [`AdhesionMatrix`] as written in a configuration: a single penalty or
+/// the rows of the matrix.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawAdhesionMatrix {
+	Uniform(f32),
+	Rows(Vec<Vec<f32>>),
+}
+
+impl TryFrom<RawAdhesionMatrix> for AdhesionMatrix {
+	type Error = Error;
+
+	fn try_from(value: RawAdhesionMatrix) -> Result<Self, Error> {
+		match value {
+			RawAdhesionMatrix::Uniform(penalty) => Ok(Self::uniform(penalty)),
+			RawAdhesionMatrix::Rows(rows) => Self::new(&rows),
+		}
+	}
+}
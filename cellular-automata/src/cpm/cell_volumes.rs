@@ -1,20 +1,20 @@
 use crate::{
-	cpm::CPMCell,
+	cpm::{
+		registry::{Divergence, Registry},
+		CPMCell,
+	},
 	world::{Coord, World},
 };
 
-pub struct CellVolumes(Box<[u32]>);
+/// The number of pixels of every (non-background) cell.
+pub struct CellVolumes(Registry<u32>);
 
 impl CellVolumes {
 	#[must_use]
 	pub fn from_world<const W: usize, const H: usize, C: CPMCell>(world: &World<W, H, C>) -> Self {
-		let mut volumes = vec![0; C::MAX_ID + 1].into_boxed_slice();
-
-		for cell in world.img.pixels().filter(|c| !c.is_bg()) {
-			volumes[cell.id()] += 1;
-		}
-
-		Self(volumes)
+		let mut volumes = Self(Registry::new());
+		volumes.recalculate(world);
+		volumes
 	}
 
 	pub fn update<const W: usize, const H: usize, C: CPMCell>(
@@ -26,10 +26,10 @@ impl CellVolumes {
 		_dest_idx: Coord,
 	) {
 		if !src.is_bg() {
-			self.0[src.id()] += 1;
+			*self.0.get_mut(src.id()) += 1;
 		}
 		if !dest.is_bg() {
-			self.0[dest.id()] -= 1;
+			*self.0.get_mut(dest.id()) -= 1;
 		}
 	}
 
@@ -37,14 +37,25 @@ impl CellVolumes {
 		&mut self,
 		world: &World<W, H, C>,
 	) {
-		self.0 = vec![0; C::MAX_ID + 1].into_boxed_slice();
+		self.0.clear();
 		for cell in world.img.pixels().filter(|c| !c.is_bg()) {
-			self.0[cell.id()] += 1;
+			*self.0.get_mut(cell.id()) += 1;
 		}
 	}
 
+	/// Compares the incrementally updated volumes with those of `world`.
+	///
+	/// # Errors
+	/// Returns the first cell whose volume differs.
+	pub fn verify<const W: usize, const H: usize, C: CPMCell>(
+		&self,
+		world: &World<W, H, C>,
+	) -> Result<(), Divergence> {
+		Divergence::check("volume", &self.0, &Self::from_world(world).0)
+	}
+
 	#[inline]
 	pub fn get<C: CPMCell>(&self, cell: C) -> u32 {
-		self.0[cell.id()]
+		self.0.get(cell.id())
 	}
 }
@@ -0,0 +1,153 @@
+use crate::{
+	cpm::{registry::Registry, CPMCell},
+	world::{Coord, World},
+};
+
+/// Returns the centroid of `pixels` in a `W` x `H` world, taking into account
+/// that they may wrap around its edges.
+#[must_use]
+pub fn centroid<const W: usize, const H: usize>(pixels: &[Coord]) -> Option<(f32, f32)> {
+	let &origin = pixels.first()?;
+	let (mut sum_x, mut sum_y) = (0, 0);
+	for &idx in pixels {
+		let (dx, dy) = World::<W, H, bool>::displacement(origin, idx);
+		sum_x += i64::from(dx);
+		sum_y += i64::from(dy);
+	}
+	let n = pixels.len() as f32;
+	Some((
+		(origin.0 as f32 + sum_x as f32 / n).rem_euclid(W as f32),
+		(origin.1 as f32 + sum_y as f32 / n).rem_euclid(H as f32),
+	))
+}
+
+/// Returns the shortest displacement from centroid `from` to centroid `to` in
+/// a `W` x `H` world.
+#[must_use]
+pub fn centroid_displacement<const W: usize, const H: usize>(
+	from: (f32, f32),
+	to: (f32, f32),
+) -> (f32, f32) {
+	let wrap = |d: f32, size: usize| {
+		let size = size as f32;
+		(d + size / 2.0).rem_euclid(size) - size / 2.0
+	};
+	(wrap(to.0 - from.0, W), wrap(to.1 - from.1, H))
+}
+
+/// The running sum of the positions of the pixels of a cell, relative to one
+/// of its pixels.
+#[derive(Clone, Copy, Default)]
+struct Accumulator {
+	anchor: Coord,
+	sum_x: i64,
+	sum_y: i64,
+	n: u32,
+}
+
+/// The centroid of every cell, updated incrementally like
+/// [`CellVolumes`](super::cell_volumes::CellVolumes). Cells may wrap around
+/// the edges of the world, as long as they are smaller than half of it.
+pub struct CellCentroids(Registry<Accumulator>);
+
+impl CellCentroids {
+	#[must_use]
+	pub fn from_world<const W: usize, const H: usize, C: CPMCell>(world: &World<W, H, C>) -> Self {
+		let mut centroids = Self(Registry::new());
+		centroids.recalculate(world);
+		centroids
+	}
+
+	pub fn update<const W: usize, const H: usize, C: CPMCell>(
+		&mut self,
+		_world: &World<W, H, C>,
+		src: C,
+		dest: C,
+		_src_idx: Coord,
+		dest_idx: Coord,
+	) {
+		if !src.is_bg() {
+			self.add::<W, H>(src.id(), dest_idx);
+		}
+		if !dest.is_bg() {
+			self.remove::<W, H>(dest.id(), dest_idx);
+		}
+	}
+
+	pub fn recalculate<const W: usize, const H: usize, C: CPMCell>(
+		&mut self,
+		world: &World<W, H, C>,
+	) {
+		self.0.clear();
+		for idx in world.positions(|c| !c.is_bg()) {
+			self.add::<W, H>(world.get_cell(idx).id(), idx);
+		}
+	}
+
+	/// Returns the centroid of `cell`, or [`None`] if it has no pixels.
+	#[must_use]
+	pub fn get<const W: usize, const H: usize, C: CPMCell>(&self, cell: C) -> Option<(f32, f32)> {
+		Self::centroid::<W, H>(&self.0.get(cell.id()))
+	}
+
+	/// Returns the ID and centroid of every cell with at least one pixel.
+	pub fn iter<const W: usize, const H: usize>(
+		&self,
+	) -> impl Iterator<Item = (usize, (f32, f32))> + '_ {
+		self.0
+			.iter()
+			.filter_map(|(id, acc)| Some((id, Self::centroid::<W, H>(&acc)?)))
+	}
+
+	fn centroid<const W: usize, const H: usize>(acc: &Accumulator) -> Option<(f32, f32)> {
+		if acc.n == 0 {
+			return None;
+		}
+		let n = acc.n as f32;
+		Some((
+			(acc.anchor.0 as f32 + acc.sum_x as f32 / n).rem_euclid(W as f32),
+			(acc.anchor.1 as f32 + acc.sum_y as f32 / n).rem_euclid(H as f32),
+		))
+	}
+
+	fn add<const W: usize, const H: usize>(&mut self, id: usize, idx: Coord) {
+		let acc = self.0.get_mut(id);
+		if acc.n == 0 {
+			*acc = Accumulator {
+				anchor: idx,
+				..Accumulator::default()
+			};
+		}
+		let (dx, dy) = World::<W, H, bool>::displacement(acc.anchor, idx);
+		acc.sum_x += i64::from(dx);
+		acc.sum_y += i64::from(dy);
+		acc.n += 1;
+		Self::recentre::<W, H>(acc);
+	}
+
+	fn remove<const W: usize, const H: usize>(&mut self, id: usize, idx: Coord) {
+		let acc = self.0.get_mut(id);
+		let (dx, dy) = World::<W, H, bool>::displacement(acc.anchor, idx);
+		acc.sum_x -= i64::from(dx);
+		acc.sum_y -= i64::from(dy);
+		acc.n -= 1;
+		Self::recentre::<W, H>(acc);
+	}
+
+	/// Moves the anchor to the pixel closest to the centroid once the cell has
+	/// moved away from it, so that displacements from the anchor stay short.
+	fn recentre<const W: usize, const H: usize>(acc: &mut Accumulator) {
+		if acc.n == 0 {
+			return;
+		}
+		let n = i64::from(acc.n);
+		let (shift_x, shift_y) = (acc.sum_x / n, acc.sum_y / n);
+		if shift_x.abs() < 2 && shift_y.abs() < 2 {
+			return;
+		}
+		acc.anchor =
+			World::<W, H, bool>::get_neighbour_idx(acc.anchor, (shift_x as i32, shift_y as i32));
+		acc.sum_x -= shift_x * n;
+		acc.sum_y -= shift_y * n;
+	}
+}
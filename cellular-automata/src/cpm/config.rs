@@ -0,0 +1,284 @@
+use std::{fs, io, path::Path};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{
+	cpm::{
+		cell_types::{AdhesionMatrix, PerType},
+		generic::GenericCPM,
+		lifecycle::Lifecycle,
+		terms::{
+			ActTerm, AdhesionTerm, ChemotaxisTerm, ConnectivityTerm, PerimeterTerm,
+			PersistenceTerm, VolumeTerm,
+		},
+		CPMCell,
+	},
+	pde::diffusion::Diffusion,
+	world::World,
+};
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error(transparent)]
+	Toml(#[from] toml::de::Error),
+
+	#[error(transparent)]
+	Io(#[from] io::Error),
+
+	#[error("`{parameter}` has values for {found} cell types, expected {expected}")]
+	TypeCount {
+		parameter: &'static str,
+		found: usize,
+		expected: usize,
+	},
+}
+
+/// The parameters of a single [`Term`](super::terms::Term), tagged by `type`.
+/// Apart from the adhesion matrix, every parameter is either a single value
+/// or a list with a value per cell type (see [`PerType`]).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum TermConfig {
+	Adhesion {
+		#[serde(alias = "penalty")]
+		j: AdhesionMatrix,
+	},
+	Volume {
+		target: PerType<u32>,
+		lambda: PerType<f32>,
+	},
+	Perimeter {
+		target: PerType<u32>,
+		lambda: PerType<f32>,
+	},
+	Act {
+		lambda: PerType<f32>,
+		max_act: PerType<u8>,
+	},
+	Chemotaxis {
+		lambda: PerType<f32>,
+		#[serde(default)]
+		secretion: PerType<f32>,
+		diffusion: f32,
+		#[serde(default)]
+		decay: f32,
+		#[serde(default = "one")]
+		dt: f32,
+		#[serde(default)]
+		initial: f32,
+	},
+	Connectivity {
+		#[serde(default = "infinity")]
+		penalty: PerType<f32>,
+	},
+	Persistence {
+		lambda: PerType<f32>,
+		memory: PerType<f32>,
+		#[serde(default)]
+		noise: f32,
+	},
+}
+
+impl TermConfig {
+	/// Returns the number of values of the parameters that are given per
+	/// type, by name.
+	fn type_counts(&self) -> Vec<(&'static str, Option<usize>)> {
+		match self {
+			TermConfig::Adhesion { j } => vec![("adhesion.j", j.n_types())],
+			TermConfig::Volume { target, lambda } => vec![
+				("volume.target", target.n_types()),
+				("volume.lambda", lambda.n_types()),
+			],
+			TermConfig::Perimeter { target, lambda } => vec![
+				("perimeter.target", target.n_types()),
+				("perimeter.lambda", lambda.n_types()),
+			],
+			TermConfig::Act { lambda, max_act } => vec![
+				("act.lambda", lambda.n_types()),
+				("act.max_act", max_act.n_types()),
+			],
+			TermConfig::Chemotaxis {
+				lambda, secretion, ..
+			} => vec![
+				("chemotaxis.lambda", lambda.n_types()),
+				("chemotaxis.secretion", secretion.n_types()),
+			],
+			TermConfig::Connectivity { penalty } => {
+				vec![("connectivity.penalty", penalty.n_types())]
+			}
+			TermConfig::Persistence { lambda, memory, .. } => vec![
+				("persistence.lambda", lambda.n_types()),
+				("persistence.memory", memory.n_types()),
+			],
+		}
+	}
+}
+
+fn infinity() -> PerType<f32> {
+	PerType::All(f32::INFINITY)
+}
+
+fn one() -> f32 {
+	1.0
+}
+
+/// The description of a [`GenericCPM`], for example:
+///
+/// ```toml
+/// temperature = 20.0
+///
+/// # Types: background, cell, obstacle
+/// [[terms]]
+/// type = "adhesion"
+/// j = [[0.0, 20.0, 20.0], [20.0, 20.0, 200.0], [20.0, 200.0, 20.0]]
+///
+/// [[terms]]
+/// type = "volume"
+/// target = [0, 500, 250]
+/// lambda = 50.0
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+	pub temperature: f32,
+
+	/// Whether to check the delta energies of every accepted copy (see
+	/// [`GenericCPM::verify`]).
+	#[serde(default)]
+	pub verify: bool,
+
+	/// Every how many steps to check the incrementally updated state of the
+	/// terms, instead of the default of [`GenericCPM::verify_interval`]. Zero
+	/// disables the checks.
+	#[serde(default)]
+	pub verify_interval: Option<usize>,
+
+	/// When cells divide and die, if at all.
+	#[serde(default)]
+	pub lifecycle: Option<Lifecycle>,
+
+	#[serde(default)]
+	pub terms: Vec<TermConfig>,
+}
+
+impl Config {
+	/// Parses a [`Config`] from a TOML string.
+	///
+	/// # Errors
+	/// Returns an error if `s` is not valid TOML, or does not describe a
+	/// [`Config`].
+	pub fn from_toml(s: &str) -> Result<Self, Error> {
+		Ok(toml::from_str(s)?)
+	}
+
+	/// Reads a [`Config`] from a TOML file.
+	///
+	/// # Errors
+	/// Returns an error if the file cannot be read or parsed.
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+		Self::from_toml(&fs::read_to_string(path)?)
+	}
+
+	/// Builds the model, in the order of the terms. Terms may add layers to
+	/// `world`.
+	///
+	/// # Errors
+	/// Returns an error if the parameters given per type do not all have a
+	/// value for the same number of types, or for every type in `world`.
+	pub fn build<const W: usize, const H: usize, C: CPMCell + 'static>(
+		&self,
+		world: &mut World<W, H, C>,
+	) -> Result<GenericCPM<W, H, C>, Error> {
+		self.check_types(world)?;
+		let mut model = GenericCPM::new(self.temperature);
+		model.verify = self.verify;
+		if let Some(interval) = self.verify_interval {
+			model.verify_interval = (interval > 0).then_some(interval);
+		}
+		model.lifecycle.clone_from(&self.lifecycle);
+		for term in &self.terms {
+			match term.clone() {
+				TermConfig::Adhesion { j } => model.add_term(AdhesionTerm::new(j)),
+				TermConfig::Volume { target, lambda } => {
+					model.add_term(VolumeTerm::new(target, lambda, world));
+				}
+				TermConfig::Perimeter { target, lambda } => {
+					model.add_term(PerimeterTerm::new(target, lambda, world));
+				}
+				TermConfig::Act { lambda, max_act } => {
+					model.add_term(ActTerm::new(lambda, max_act, world));
+				}
+				TermConfig::Chemotaxis {
+					lambda,
+					secretion,
+					diffusion,
+					decay,
+					dt,
+					initial,
+				} => {
+					let diffusion = Diffusion {
+						coefficient: diffusion,
+						decay,
+						..Diffusion::default()
+					};
+					model.add_term(ChemotaxisTerm::new(
+						lambda, secretion, diffusion, dt, initial, world,
+					));
+				}
+				TermConfig::Connectivity { penalty } => {
+					model.add_term(ConnectivityTerm::new(penalty));
+				}
+				TermConfig::Persistence {
+					lambda,
+					memory,
+					noise,
+				} => {
+					model.add_term(PersistenceTerm::new(lambda, memory, noise, world));
+				}
+			}
+		}
+		Ok(model)
+	}
+
+	/// Checks that every list of per-type values has a value for every type:
+	/// as many as the longest list, and at least one more than the highest
+	/// type in `world`.
+	fn check_types<const W: usize, const H: usize, C: CPMCell>(
+		&self,
+		world: &World<W, H, C>,
+	) -> Result<(), Error> {
+		let mut counts: Vec<_> = self
+			.terms
+			.iter()
+			.flat_map(TermConfig::type_counts)
+			.collect();
+		if let Some(lifecycle) = &self.lifecycle {
+			counts.push((
+				"lifecycle.division_volume",
+				lifecycle.division_volume.n_types(),
+			));
+			counts.push(("lifecycle.death_rate", lifecycle.death_rate.n_types()));
+		}
+		let counts: Vec<_> = counts
+			.into_iter()
+			.filter_map(|(parameter, found)| Some((parameter, found?)))
+			.collect();
+
+		let in_world = world.img.pixels().map(|cell| cell.cell_type() + 1).max();
+		let expected = counts
+			.iter()
+			.map(|&(_, found)| found)
+			.chain(in_world)
+			.max()
+			.unwrap_or_default();
+		match counts.into_iter().find(|&(_, found)| found != expected) {
+			Some((parameter, found)) => Err(Error::TypeCount {
+				parameter,
+				found,
+				expected,
+			}),
+			None => Ok(()),
+		}
+	}
+}
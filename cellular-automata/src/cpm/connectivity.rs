@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+	cpm::CPMCell,
+	world::{Coord, World},
+};
+
+/// The offsets of the 8 neighbours of a pixel, in order around it. Pixels
+/// that follow each other are 4-connected, and the even ones are the von
+/// Neumann neighbours.
+const RING: [(i32, i32); 8] = [
+	(0, -1),
+	(1, -1),
+	(1, 0),
+	(1, 1),
+	(0, 1),
+	(-1, 1),
+	(-1, 0),
+	(-1, -1),
+];
+
+/// Returns whether removing the pixel at `idx` from its cell would split the
+/// cell into pieces that are not 4-connected.
+///
+/// This first checks whether the von Neumann neighbours of the same cell are
+/// connected around the pixel, and only if not, whether they are connected
+/// through the rest of the cell (with a flood fill).
+#[must_use]
+pub fn would_split<const W: usize, const H: usize, C: CPMCell>(
+	world: &World<W, H, C>,
+	idx: Coord,
+) -> bool {
+	let cell = world.get_cell(idx);
+	let ring =
+		RING.map(|offset| world.get_cell(World::<W, H, C>::get_neighbour_idx(idx, offset)) == cell);
+	let n_touching = ring.iter().step_by(2).filter(|&&same| same).count();
+	if n_touching <= 1 {
+		return false;
+	}
+	let Some(start) = ring.iter().position(|&same| !same) else {
+		// Surrounded by the same cell
+		return false;
+	};
+
+	// Label the runs of the same cell around the pixel, and check whether
+	// all von Neumann neighbours are in the same one
+	let mut runs = HashSet::new();
+	let mut run = 0;
+	for i in (1..=8).map(|k| (start + k) % 8) {
+		if !ring[i] {
+			run += 1;
+		} else if i % 2 == 0 {
+			runs.insert(run);
+		}
+	}
+	if runs.len() <= 1 {
+		return false;
+	}
+
+	let targets: Vec<Coord> = RING
+		.iter()
+		.step_by(2)
+		.zip(ring.iter().step_by(2))
+		.filter(|(_, &same)| same)
+		.map(|(&offset, _)| World::<W, H, C>::get_neighbour_idx(idx, offset))
+		.collect();
+	!connected(world, cell, idx, &targets)
+}
+
+/// Returns whether all `targets` of `cell` are 4-connected to each other
+/// without going through `removed`.
+fn connected<const W: usize, const H: usize, C: CPMCell>(
+	world: &World<W, H, C>,
+	cell: C,
+	removed: Coord,
+	targets: &[Coord],
+) -> bool {
+	let mut remaining: HashSet<Coord> = targets[1..].iter().copied().collect();
+	let mut visited = HashSet::from([removed, targets[0]]);
+	let mut queue = VecDeque::from([targets[0]]);
+	while let Some(idx) = queue.pop_front() {
+		for neighbour in World::<W, H, C>::get_von_neumann_neighbours_idx(idx) {
+			if world.get_cell(neighbour) == cell && visited.insert(neighbour) {
+				remaining.remove(&neighbour);
+				if remaining.is_empty() {
+					return true;
+				}
+				queue.push_back(neighbour);
+			}
+		}
+	}
+	remaining.is_empty()
+}
+
+/// Returns whether a pixel of `cell` at `idx` would be 4-connected to the
+/// rest of the cell.
+#[must_use]
+pub fn touches<const W: usize, const H: usize, C: CPMCell>(
+	world: &World<W, H, C>,
+	idx: Coord,
+	cell: C,
+) -> bool {
+	world.get_von_neumann_neighbours(idx).contains(&cell)
+}
+
+/// Returns the cells that consist of more than one 4-connected piece.
+#[must_use]
+pub fn fragmented_cells<const W: usize, const H: usize, C: CPMCell>(
+	world: &World<W, H, C>,
+) -> Vec<C> {
+	let mut pieces: HashMap<usize, (C, usize)> = HashMap::new();
+	let mut visited = HashSet::new();
+	for start in world.positions(|c| !c.is_bg()) {
+		if !visited.insert(start) {
+			continue;
+		}
+		let cell = world.get_cell(start);
+		pieces.entry(cell.id()).or_insert((cell, 0)).1 += 1;
+
+		let mut queue = VecDeque::from([start]);
+		while let Some(idx) = queue.pop_front() {
+			for neighbour in World::<W, H, C>::get_von_neumann_neighbours_idx(idx) {
+				if world.get_cell(neighbour) == cell && visited.insert(neighbour) {
+					queue.push_back(neighbour);
+				}
+			}
+		}
+	}
+	pieces
+		.into_values()
+		.filter(|&(_, n)| n > 1)
+		.map(|(cell, _)| cell)
+		.collect()
+}
@@ -0,0 +1,424 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use thiserror::Error;
+
+use crate::{
+	cpm::{
+		centroids::CellCentroids,
+		id_pool::IdPool,
+		lifecycle::{split, DivisionAxis, Lifecycle},
+		registry::Divergence,
+		terms::Term,
+		trajectory::Trajectories,
+		CPMCell, CPM,
+	},
+	world::{Coord, World},
+};
+
+/// At most this many mismatches are kept until they are taken, so that a term
+/// with a wrong delta does not fill up the memory.
+pub const MAX_MISMATCHES: usize = 1000;
+
+/// How often (in steps) debug builds check the incrementally updated state of
+/// the terms by default (see [`GenericCPM::verify_interval`]).
+pub const DEBUG_VERIFY_INTERVAL: usize = 100;
+
+/// A copy whose delta energy, as reported by a term, differs from the change
+/// in the total energy of that term.
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+#[error("{term} delta of {src_idx:?} into {dest_idx:?} is {delta}, but should be {actual}")]
+pub struct Mismatch {
+	pub term: &'static str,
+	pub src_idx: Coord,
+	pub dest_idx: Coord,
+
+	/// The delta energy reported by [`Term::delta`].
+	pub delta: f32,
+
+	/// The difference of the total energies after and before the copy.
+	pub actual: f64,
+}
+
+/// A CPM whose Hamiltonian is the sum of a list of [`Term`]s, so that models
+/// can be assembled at runtime (for example from a
+/// [`Config`](super::config::Config)).
+pub struct GenericCPM<const W: usize, const H: usize, C: CPMCell> {
+	pub temperature: f32,
+	terms: Vec<Box<dyn Term<W, H, C>>>,
+
+	/// Whether to check every accepted copy with [`GenericCPM::verify_copy`].
+	/// This computes the total energy twice per copy, so it is very slow.
+	pub verify: bool,
+	mismatches: Vec<Mismatch>,
+
+	/// Every how many steps to check the incrementally updated state of every
+	/// term with [`Term::verify`], if at all (see
+	/// [`GenericCPM::take_divergences`]). By default every
+	/// [`DEBUG_VERIFY_INTERVAL`] steps in debug builds, and never in release
+	/// builds.
+	pub verify_interval: Option<usize>,
+	divergences: Vec<(usize, Divergence)>,
+	steps: usize,
+
+	/// When cells divide and die, if at all. This is applied after every step.
+	pub lifecycle: Option<Lifecycle>,
+
+	/// The IDs in use, created when the first cell divides.
+	ids: Option<IdPool>,
+
+	/// The dying cells, by ID.
+	dying: HashMap<usize, C>,
+
+	/// The centroids and trajectories of all cells, once tracking is enabled.
+	tracking: Option<(CellCentroids, Trajectories)>,
+}
+
+impl<const W: usize, const H: usize, C: CPMCell> GenericCPM<W, H, C> {
+	/// Creates a model without any terms.
+	#[must_use]
+	pub fn new(temperature: f32) -> Self {
+		Self {
+			temperature,
+			terms: Vec::new(),
+			verify: false,
+			mismatches: Vec::new(),
+			verify_interval: cfg!(debug_assertions).then_some(DEBUG_VERIFY_INTERVAL),
+			divergences: Vec::new(),
+			steps: 0,
+			lifecycle: None,
+			ids: None,
+			dying: HashMap::new(),
+			tracking: None,
+		}
+	}
+
+	pub fn add_term<T: Term<W, H, C> + 'static>(&mut self, term: T) {
+		self.terms.push(Box::new(term));
+	}
+
+	/// Like [`GenericCPM::add_term`], but consumes and returns the model.
+	#[must_use]
+	pub fn with_term<T: Term<W, H, C> + 'static>(mut self, term: T) -> Self {
+		self.add_term(term);
+		self
+	}
+
+	#[must_use]
+	pub fn terms(&self) -> &[Box<dyn Term<W, H, C>>] {
+		&self.terms
+	}
+
+	/// Starts keeping track of the centroid of every cell, which is recorded
+	/// after every step from now on.
+	pub fn track(&mut self, world: &World<W, H, C>) {
+		let centroids = CellCentroids::from_world(world);
+		let mut trajectories = Trajectories::new();
+		trajectories.record::<W, H>(&centroids);
+		self.tracking = Some((centroids, trajectories));
+	}
+
+	/// Returns the current centroids, if tracking is enabled.
+	#[must_use]
+	pub fn centroids(&self) -> Option<&CellCentroids> {
+		self.tracking.as_ref().map(|(centroids, _)| centroids)
+	}
+
+	/// Returns the trajectories recorded so far, if tracking is enabled.
+	#[must_use]
+	pub fn trajectories(&self) -> Option<&Trajectories> {
+		self.tracking.as_ref().map(|(_, trajectories)| trajectories)
+	}
+
+	/// Changes the pixel at `idx` to `cell`, and updates the state of every
+	/// term as if `cell` had been copied into it from the pixel itself (see
+	/// [`Term::update`]).
+	pub fn set_cell(&mut self, world: &mut World<W, H, C>, idx: Coord, cell: C) {
+		let old = world.get_cell(idx);
+		if old == cell {
+			return;
+		}
+		self.update_state(world, cell, old, idx, idx);
+		world.img[idx] = cell;
+	}
+
+	/// Updates the terms and centroids for a copy, before it is applied.
+	fn update_state(
+		&mut self,
+		world: &mut World<W, H, C>,
+		src: C,
+		dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) {
+		for term in &mut self.terms {
+			term.update(world, src, dest, src_idx, dest_idx);
+		}
+		if let Some((centroids, _)) = &mut self.tracking {
+			centroids.update(world, src, dest, src_idx, dest_idx);
+		}
+	}
+
+	/// Divides `cell` in two along `axis`, and returns the new daughter cell,
+	/// or [`None`] if there is no ID available for it or the cell is too small
+	/// to divide.
+	pub fn divide<R: Rng>(
+		&mut self,
+		world: &mut World<W, H, C>,
+		cell: C,
+		axis: DivisionAxis,
+		rng: &mut R,
+	) -> Option<C> {
+		let pixels = world.positions(|c| c == cell);
+		self.divide_pixels(world, cell, &pixels, axis, rng)
+	}
+
+	fn divide_pixels<R: Rng>(
+		&mut self,
+		world: &mut World<W, H, C>,
+		cell: C,
+		pixels: &[Coord],
+		axis: DivisionAxis,
+		rng: &mut R,
+	) -> Option<C> {
+		let half = split::<W, H, _>(pixels, axis, rng);
+		if half.is_empty() || half.len() == pixels.len() {
+			return None;
+		}
+		let daughter = self
+			.ids
+			.get_or_insert_with(|| IdPool::from_world(world))
+			.allocate(cell)?;
+		for idx in half {
+			self.set_cell(world, idx, daughter);
+		}
+		Some(daughter)
+	}
+
+	/// Lets `cell` die: its target size becomes zero, and its ID is reused once
+	/// it has disappeared.
+	pub fn kill(&mut self, cell: C) {
+		if self.dying.insert(cell.id(), cell).is_none() {
+			for term in &mut self.terms {
+				term.on_apoptosis(cell);
+			}
+		}
+	}
+
+	/// Returns whether `cell` is dying.
+	#[must_use]
+	pub fn is_dying(&self, cell: C) -> bool {
+		self.dying.contains_key(&cell.id())
+	}
+
+	/// Releases the IDs of cells that have disappeared, and lets cells divide
+	/// and die according to the lifecycle.
+	fn apply_lifecycle(&mut self, world: &mut World<W, H, C>) {
+		if self.lifecycle.is_none() && self.ids.is_none() && self.dying.is_empty() {
+			return;
+		}
+
+		let mut cells: HashMap<usize, (C, Vec<Coord>)> = HashMap::new();
+		for idx in world.positions(|c| !c.is_bg()) {
+			let cell = world.get_cell(idx);
+			cells
+				.entry(cell.id())
+				.or_insert((cell, Vec::new()))
+				.1
+				.push(idx);
+		}
+
+		if self.lifecycle.is_some() {
+			self.ids.get_or_insert_with(|| IdPool::from_world(world));
+		}
+		if let Some(ids) = &mut self.ids {
+			let removed: Vec<usize> = ids.used().filter(|id| !cells.contains_key(id)).collect();
+			for id in removed {
+				ids.release(id);
+				if let Some((_, trajectories)) = &mut self.tracking {
+					trajectories.finish(id);
+				}
+			}
+		}
+		let removed: Vec<usize> = self
+			.dying
+			.keys()
+			.filter(|id| !cells.contains_key(id))
+			.copied()
+			.collect();
+		for id in removed {
+			if let Some(cell) = self.dying.remove(&id) {
+				for term in &mut self.terms {
+					term.on_removed(cell);
+				}
+			}
+		}
+
+		let Some(lifecycle) = self.lifecycle.clone() else {
+			return;
+		};
+		let mut rng = rand::thread_rng();
+		let mut cells: Vec<_> = cells.into_values().collect();
+		cells.sort_unstable_by_key(|(cell, _)| cell.id());
+		for (cell, pixels) in cells {
+			if self.is_dying(cell) {
+				continue;
+			}
+			let cell_type = cell.cell_type();
+			if rng.gen::<f32>() < lifecycle.death_rate.get(cell_type) {
+				self.kill(cell);
+				continue;
+			}
+			let division_volume = lifecycle.division_volume.get(cell_type) as usize;
+			if division_volume > 0 && pixels.len() >= division_volume {
+				self.divide_pixels(world, cell, &pixels, lifecycle.axis, &mut rng);
+			}
+		}
+	}
+
+	/// Returns the name and total energy of every term (see [`Term::energy`]).
+	#[must_use]
+	pub fn energies(&self, world: &World<W, H, C>) -> Vec<(&'static str, Option<f64>)> {
+		self.terms
+			.iter()
+			.map(|term| (term.name(), term.energy(world)))
+			.collect()
+	}
+
+	/// Returns the total energy of `world`, summed over all terms that
+	/// correspond to an energy.
+	#[must_use]
+	pub fn energy(&self, world: &World<W, H, C>) -> f64 {
+		self.terms
+			.iter()
+			.filter_map(|term| term.energy(world))
+			.sum()
+	}
+
+	/// Returns the mismatches found since the last call (at most
+	/// [`MAX_MISMATCHES`]), if [`GenericCPM::verify`] is enabled.
+	pub fn take_mismatches(&mut self) -> Vec<Mismatch> {
+		std::mem::take(&mut self.mismatches)
+	}
+
+	/// Returns the divergences found since the last call, with the number of
+	/// steps after which they were found, if [`GenericCPM::verify_interval`]
+	/// is set.
+	pub fn take_divergences(&mut self) -> Vec<(usize, Divergence)> {
+		std::mem::take(&mut self.divergences)
+	}
+
+	/// Performs the copy from `src_idx` into `dest_idx` (updating the state of
+	/// every term), and returns the terms whose delta energy differs from the
+	/// change in their total energy. Terms without an energy are skipped.
+	pub fn verify_copy(
+		&mut self,
+		world: &mut World<W, H, C>,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) -> Vec<Mismatch> {
+		let src = world.get_cell(src_idx);
+		let dest = world.get_cell(dest_idx);
+		let before: Vec<_> = self
+			.terms
+			.iter()
+			.map(|term| {
+				let delta = term.delta(world, src, dest, src_idx, dest_idx);
+				(delta, term.energy(world))
+			})
+			.collect();
+
+		self.update_state(world, src, dest, src_idx, dest_idx);
+		world.img[dest_idx] = src;
+
+		self.terms
+			.iter()
+			.zip(before)
+			.filter_map(|(term, (delta, before))| {
+				let before = before?;
+				let actual = term.energy(world)? - before;
+				// The totals are summed in f64, so the difference is exact
+				// enough; only allow for the rounding of the f32 delta itself
+				let tolerance = 1e-3 + 1e-4 * f64::from(delta).abs();
+				((f64::from(delta) - actual).abs() > tolerance).then_some(Mismatch {
+					term: term.name(),
+					src_idx,
+					dest_idx,
+					delta,
+					actual,
+				})
+			})
+			.collect()
+	}
+}
+
+impl<const W: usize, const H: usize, C: CPMCell> CPM<W, H> for GenericCPM<W, H, C> {
+	type C = C;
+
+	fn hamiltonian(
+		&self,
+		world: &World<W, H, C>,
+		src: C,
+		dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) -> f32 {
+		self.terms
+			.iter()
+			.map(|term| term.delta(world, src, dest, src_idx, dest_idx))
+			.sum()
+	}
+
+	#[inline]
+	fn get_temperature(&self) -> f32 {
+		self.temperature
+	}
+
+	fn update(
+		&mut self,
+		world: &mut World<W, H, C>,
+		src: C,
+		dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) -> C {
+		if self.verify {
+			let mismatches = self.verify_copy(world, src_idx, dest_idx);
+			let room = MAX_MISMATCHES.saturating_sub(self.mismatches.len());
+			self.mismatches.extend(mismatches.into_iter().take(room));
+		} else {
+			self.update_state(world, src, dest, src_idx, dest_idx);
+		}
+		src
+	}
+
+	fn after_step(&mut self, world: &mut World<W, H, C>) {
+		for term in &mut self.terms {
+			term.after_step(world);
+		}
+		self.steps += 1;
+		if let Some(interval) = self.verify_interval {
+			if self.steps.is_multiple_of(interval) {
+				let divergences = self
+					.terms
+					.iter()
+					.filter_map(|term| term.verify(world).err());
+				self.divergences
+					.extend(divergences.map(|divergence| (self.steps, divergence)));
+			}
+		}
+		self.apply_lifecycle(world);
+		if let Some((centroids, trajectories)) = &mut self.tracking {
+			trajectories.record::<W, H>(centroids);
+		}
+	}
+
+	/// Uses the colour of the first term that determines it, or else the
+	/// colour of the cell.
+	fn colour(&self, world: &World<W, H, C>, idx: Coord, cell: C) -> [u8; 4] {
+		self.terms
+			.iter()
+			.find_map(|term| term.colour(world, idx, cell))
+			.unwrap_or_else(|| cell.colour())
+	}
+}
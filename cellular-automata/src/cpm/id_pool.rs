@@ -0,0 +1,49 @@
+use crate::{
+	cpm::{registry::Registry, CPMCell},
+	world::World,
+};
+
+/// Keeps track of which cell IDs are in use, so that new cells can be given a
+/// fresh ID and the IDs of removed cells can be reused.
+pub struct IdPool {
+	used: Registry<bool>,
+}
+
+impl IdPool {
+	/// Creates a pool with the IDs of all cells in `world` in use.
+	#[must_use]
+	pub fn from_world<const W: usize, const H: usize, C: CPMCell>(world: &World<W, H, C>) -> Self {
+		let mut used = Registry::new();
+		for cell in world.img.pixels().filter(|c| !c.is_bg()) {
+			*used.get_mut(cell.id()) = true;
+		}
+		Self { used }
+	}
+
+	/// Returns a new cell of the same type as `parent` with an unused ID, or
+	/// [`None`] if there are no IDs left for that type. The lowest unused ID
+	/// that `parent` accepts is chosen, and the pool grows if there is none.
+	pub fn allocate<C: CPMCell>(&mut self, parent: C) -> Option<C> {
+		let (id, cell) = (0..=self.used.len())
+			.filter(|&id| !self.used.get(id))
+			.find_map(|id| parent.with_id(id).filter(|c| !c.is_bg()).map(|c| (id, c)))?;
+		*self.used.get_mut(id) = true;
+		Some(cell)
+	}
+
+	/// Makes `id` available again.
+	pub fn release(&mut self, id: usize) {
+		*self.used.get_mut(id) = false;
+	}
+
+	#[inline]
+	#[must_use]
+	pub fn is_used(&self, id: usize) -> bool {
+		self.used.get(id)
+	}
+
+	/// Returns the IDs that are in use.
+	pub fn used(&self) -> impl Iterator<Item = usize> + '_ {
+		self.used.iter().filter_map(|(id, used)| used.then_some(id))
+	}
+}
@@ -0,0 +1,89 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{
+	cpm::cell_types::PerType,
+	world::{Coord, World},
+};
+
+/// The line along which a dividing cell is cut, through its centroid.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DivisionAxis {
+	/// Cut along the short axis, so that the daughters split the longest
+	/// dimension of the cell (as most cells do).
+	#[default]
+	Short,
+
+	/// Cut along the long axis.
+	Long,
+
+	/// Cut at a random angle.
+	Random,
+}
+
+/// When cells divide and die. Cells divide once their volume reaches the
+/// `division_volume` of their type (zero for never), and start to die with a
+/// probability of `death_rate` per step. Dying cells shrink to nothing, after
+/// which their ID is reused.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Lifecycle {
+	#[serde(default)]
+	pub division_volume: PerType<u32>,
+
+	#[serde(default)]
+	pub axis: DivisionAxis,
+
+	#[serde(default)]
+	pub death_rate: PerType<f32>,
+}
+
+/// Splits the `pixels` of a cell in two along `axis`, and returns the pixels
+/// on one side. The pixels may wrap around the edges of a `W` x `H` world.
+#[must_use]
+pub fn split<const W: usize, const H: usize, R: Rng>(
+	pixels: &[Coord],
+	axis: DivisionAxis,
+	rng: &mut R,
+) -> Vec<Coord> {
+	let Some(&origin) = pixels.first() else {
+		return Vec::new();
+	};
+	// Positions relative to the first pixel, taking the shortest way around
+	// the edges, so that cells on the edge are not torn apart
+	let relative: Vec<(f32, f32)> = pixels
+		.iter()
+		.map(|&idx| {
+			let (dx, dy) = World::<W, H, bool>::displacement(origin, idx);
+			(dx as f32, dy as f32)
+		})
+		.collect();
+
+	let n = relative.len() as f32;
+	let mean_x = relative.iter().map(|p| p.0).sum::<f32>() / n;
+	let mean_y = relative.iter().map(|p| p.1).sum::<f32>() / n;
+	let (mut var_x, mut var_y, mut cov) = (0.0, 0.0, 0.0);
+	for &(x, y) in &relative {
+		var_x += (x - mean_x) * (x - mean_x);
+		var_y += (y - mean_y) * (y - mean_y);
+		cov += (x - mean_x) * (y - mean_y);
+	}
+
+	// The angle of the long axis of the cell
+	let long = 0.5 * f32::atan2(2.0 * cov, var_x - var_y);
+	// The normal of the cut
+	let normal = match axis {
+		DivisionAxis::Short => long,
+		DivisionAxis::Long => long + core::f32::consts::FRAC_PI_2,
+		DivisionAxis::Random => rng.gen_range(0.0..core::f32::consts::PI),
+	};
+	let (sin, cos) = normal.sin_cos();
+
+	pixels
+		.iter()
+		.zip(relative)
+		.filter(|(_, (x, y))| (x - mean_x) * cos + (y - mean_y) * sin > 0.0)
+		.map(|(&idx, _)| idx)
+		.collect()
+}
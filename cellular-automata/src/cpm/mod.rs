@@ -1,8 +1,18 @@
 pub mod act;
 pub mod adhesion;
 pub mod cell_perimeters;
+pub mod cell_types;
 pub mod cell_volumes;
+pub mod centroids;
+pub mod config;
+pub mod connectivity;
+pub mod generic;
+pub mod id_pool;
+pub mod lifecycle;
 pub mod perimeter;
+pub mod registry;
+pub mod terms;
+pub mod trajectory;
 pub mod volume;
 
 use crate::{
@@ -11,13 +21,30 @@ use crate::{
 };
 
 pub trait CPMCell: Cell {
-	const MAX_ID: usize;
-
 	#[must_use]
 	fn is_bg(&self) -> bool;
 
+	/// Returns the ID of this cell, which is unique to it. Per-cell state is
+	/// kept in [`Registry`](registry::Registry)s indexed by it, so IDs should
+	/// be small and dense.
 	#[must_use]
 	fn id(&self) -> usize;
+
+	/// Returns the type of this cell, which selects its parameters (see
+	/// [`cell_types`]). By default, the background is type 0 and every other
+	/// cell is type 1.
+	#[must_use]
+	fn cell_type(&self) -> usize {
+		usize::from(!self.is_bg())
+	}
+
+	/// Returns a cell of the same type as this one with the given `id`, or
+	/// [`None`] if cells of this type cannot have that ID. This is used to
+	/// create new cells when a cell divides, which is not possible by default.
+	#[must_use]
+	fn with_id(&self, _id: usize) -> Option<Self> {
+		None
+	}
 }
 
 pub trait CPM<const W: usize, const H: usize> {
@@ -36,10 +63,12 @@ pub trait CPM<const W: usize, const H: usize> {
 	#[must_use]
 	fn get_temperature(&self) -> f32;
 
+	/// Called when the copy from `src_idx` into `dest_idx` is accepted, before
+	/// the world is changed. Returns the new state of `dest_idx`.
 	#[must_use]
 	fn update(
 		&mut self,
-		_world: &World<W, H, Self::C>,
+		_world: &mut World<W, H, Self::C>,
 		src: Self::C,
 		_dest: Self::C,
 		_src_idx: Coord,
@@ -50,6 +79,13 @@ pub trait CPM<const W: usize, const H: usize> {
 
 	fn after_step(&mut self, _world: &mut World<W, H, Self::C>) {}
 
+	/// Returns the colour to draw the pixel at `idx` with, which may depend on
+	/// the layers of the world. By default, this is the colour of the cell.
+	#[must_use]
+	fn colour(&self, _world: &World<W, H, Self::C>, _idx: Coord, cell: Self::C) -> [u8; 4] {
+		cell.colour()
+	}
+
 	fn step(&mut self, world: &mut World<W, H, Self::C>)
 	where
 		Self: Sized,
@@ -1,6 +1,9 @@
+use std::collections::HashMap;
+
 use crate::{
-	cpm::CPM,
+	cpm::{CPMCell, CPM},
 	world::{Coord, World},
+	Cell,
 };
 
 pub trait Perimeter<const W: usize, const H: usize>: CPM<W, H> {
@@ -23,22 +26,80 @@ pub trait Perimeter<const W: usize, const H: usize>: CPM<W, H> {
 		src_idx: Coord,
 		dest_idx: Coord,
 	) -> f32 {
-		let neighbourhood = world.get_neighbours(dest_idx);
-		let mut src_perim_delta = 0;
-		let mut dest_perim_delta = 0;
-		for n in neighbourhood {
-			src_perim_delta += if n == src { -1 } else { 1 };
-			dest_perim_delta += if n == dest { 1 } else { -1 };
-		}
-
 		let src_perim = self.perimeter(world, src_idx, src);
 		let dest_perim = self.perimeter(world, dest_idx, dest);
-		let src_gain = self
-			.get_perimeter_penalty(src, src_perim.saturating_add_signed(src_perim_delta))
-			- self.get_perimeter_penalty(src, src_perim);
-		let dest_loss = self
-			.get_perimeter_penalty(dest, dest_perim.saturating_add_signed(dest_perim_delta))
-			- self.get_perimeter_penalty(dest, dest_perim);
-		src_gain + dest_loss
+		perimeter_delta(
+			world,
+			src,
+			dest,
+			dest_idx,
+			src_perim,
+			dest_perim,
+			|cell, perimeter| self.get_perimeter_penalty(cell, perimeter),
+		)
+	}
+
+	/// Returns the total perimeter energy of `world`.
+	#[must_use]
+	fn perimeter_energy(&self, world: &World<W, H, Self::C>) -> f64 {
+		perimeter_energy(world, |cell, perimeter| {
+			self.get_perimeter_penalty(cell, perimeter)
+		})
+	}
+}
+
+/// Returns the change in perimeter energy when the pixel at `dest_idx`
+/// changes from `dest` (with perimeter `dest_perim`) to `src` (with perimeter
+/// `src_perim`).
+pub fn perimeter_delta<const W: usize, const H: usize, C: Cell, F>(
+	world: &World<W, H, C>,
+	src: C,
+	dest: C,
+	dest_idx: Coord,
+	src_perim: u32,
+	dest_perim: u32,
+	penalty: F,
+) -> f32
+where
+	F: Fn(C, u32) -> f32,
+{
+	let mut src_perim_delta = 0;
+	let mut dest_perim_delta = 0;
+	for n in world.get_neighbours(dest_idx) {
+		src_perim_delta += if n == src { -1 } else { 1 };
+		dest_perim_delta += if n == dest { 1 } else { -1 };
+	}
+
+	let src_gain =
+		penalty(src, src_perim.saturating_add_signed(src_perim_delta)) - penalty(src, src_perim);
+	let dest_loss = penalty(dest, dest_perim.saturating_add_signed(dest_perim_delta))
+		- penalty(dest, dest_perim);
+	src_gain + dest_loss
+}
+
+/// Returns the total perimeter energy of `world`: the sum of `penalty` over
+/// all non-background cells, with their perimeters (the number of neighbour
+/// pairs with a different cell, wrapping around the edges) counted from
+/// scratch.
+pub fn perimeter_energy<const W: usize, const H: usize, C: CPMCell, F>(
+	world: &World<W, H, C>,
+	penalty: F,
+) -> f64
+where
+	F: Fn(C, u32) -> f32,
+{
+	let mut perimeters = HashMap::new();
+	for idx in world.positions(|c| !c.is_bg()) {
+		let cell = world.get_cell(idx);
+		let n_different = world
+			.get_neighbours(idx)
+			.iter()
+			.filter(|&&n| n != cell)
+			.count();
+		perimeters.entry(cell.id()).or_insert((cell, 0)).1 += n_different as u32;
 	}
+	perimeters
+		.into_values()
+		.map(|(cell, perimeter)| f64::from(penalty(cell, perimeter)))
+		.sum()
 }
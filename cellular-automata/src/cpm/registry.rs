@@ -0,0 +1,85 @@
+use thiserror::Error;
+
+/// A value for every cell, indexed by [`CPMCell::id`](super::CPMCell::id).
+/// The registry grows as cells with higher IDs appear, and cells that were
+/// never stored have the default value, so there is no upper limit on IDs.
+#[derive(Debug, Clone, Default)]
+pub struct Registry<T>(Vec<T>);
+
+impl<T: Copy + Default> Registry<T> {
+	#[must_use]
+	pub fn new() -> Self {
+		Self(Vec::new())
+	}
+
+	#[inline]
+	#[must_use]
+	pub fn get(&self, id: usize) -> T {
+		self.0.get(id).copied().unwrap_or_default()
+	}
+
+	/// Returns the value of `id`, growing the registry if needed.
+	#[inline]
+	pub fn get_mut(&mut self, id: usize) -> &mut T {
+		if id >= self.0.len() {
+			self.0.resize(id + 1, T::default());
+		}
+		&mut self.0[id]
+	}
+
+	/// Resets every value to the default.
+	pub fn clear(&mut self) {
+		self.0.clear();
+	}
+
+	/// Returns one more than the highest ID that has been stored.
+	#[inline]
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	#[inline]
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// Returns every stored ID with its value.
+	pub fn iter(&self) -> impl Iterator<Item = (usize, T)> + '_ {
+		self.0.iter().copied().enumerate()
+	}
+}
+
+/// A cell whose incrementally updated value differs from the value computed
+/// from scratch, which means that an incremental update is wrong.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("{tracker} of cell {id} is {incremental}, but {actual} when computed from scratch")]
+pub struct Divergence {
+	pub tracker: &'static str,
+	pub id: usize,
+	pub incremental: u32,
+	pub actual: u32,
+}
+
+impl Divergence {
+	/// Compares the `incremental` values of a tracker with the `actual` ones.
+	///
+	/// # Errors
+	/// Returns the first ID whose values differ.
+	pub fn check(
+		tracker: &'static str,
+		incremental: &Registry<u32>,
+		actual: &Registry<u32>,
+	) -> Result<(), Self> {
+		(0..incremental.len().max(actual.len()))
+			.map(|id| Self {
+				tracker,
+				id,
+				incremental: incremental.get(id),
+				actual: actual.get(id),
+			})
+			.find(|divergence| divergence.incremental != divergence.actual)
+			.map_or(Ok(()), Err)
+	}
+}
@@ -0,0 +1,699 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::Rng;
+
+use crate::{
+	cpm::{
+		act::gm_act,
+		adhesion::{adhesion, adhesion_energy},
+		cell_perimeters::CellPerimeters,
+		cell_types::{AdhesionMatrix, PerType},
+		cell_volumes::CellVolumes,
+		centroids::{centroid_displacement, CellCentroids},
+		connectivity::{touches, would_split},
+		perimeter::{perimeter_delta, perimeter_energy},
+		registry::{Divergence, Registry},
+		volume::{volume_delta, volume_energy},
+		CPMCell,
+	},
+	layer::{Layer, LayerId},
+	pde::diffusion::Diffusion,
+	world::{Coord, World},
+};
+
+/// A single term of the Hamiltonian of a [`GenericCPM`](super::generic::GenericCPM),
+/// with its own parameters and (optionally) its own incrementally updated
+/// state.
+pub trait Term<const W: usize, const H: usize, C: CPMCell> {
+	/// A short name for the term, used when reporting energies.
+	#[must_use]
+	fn name(&self) -> &'static str;
+
+	/// Returns the total energy of this term in `world`, computed from scratch
+	/// (not from any incremental state) and summed in `f64` so that it can be
+	/// compared with single deltas, or [`None`] if the term does not
+	/// correspond to an energy (such as [`ActTerm`], whose delta is not the
+	/// difference of a potential).
+	#[must_use]
+	fn energy(&self, world: &World<W, H, C>) -> Option<f64>;
+
+	/// Returns the change in energy of this term for copying `src` at
+	/// `src_idx` into `dest` at `dest_idx`.
+	#[must_use]
+	fn delta(
+		&self,
+		world: &World<W, H, C>,
+		src: C,
+		dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) -> f32;
+
+	/// Called when the copy from `src_idx` into `dest_idx` is accepted, before
+	/// the world is changed. Both are the same pixel if it changes without a
+	/// copy, as when a cell divides.
+	fn update(
+		&mut self,
+		_world: &mut World<W, H, C>,
+		_src: C,
+		_dest: C,
+		_src_idx: Coord,
+		_dest_idx: Coord,
+	) {
+	}
+
+	/// Called after every Monte Carlo step.
+	fn after_step(&mut self, _world: &mut World<W, H, C>) {}
+
+	/// Compares the incrementally updated state of this term with the state
+	/// computed from scratch for `world`.
+	///
+	/// # Errors
+	/// Returns the first cell whose state differs.
+	fn verify(&self, _world: &World<W, H, C>) -> Result<(), Divergence> {
+		Ok(())
+	}
+
+	/// Called when `cell` starts to die. Terms that keep the size of cells
+	/// close to a target should shrink it to zero.
+	fn on_apoptosis(&mut self, _cell: C) {}
+
+	/// Called when the last pixel of `cell` is gone, after which its ID may be
+	/// reused.
+	fn on_removed(&mut self, _cell: C) {}
+
+	/// Returns the colour to draw the pixel at `idx` with, if this term
+	/// determines it (see [`CPM::colour`](super::CPM::colour)).
+	#[must_use]
+	fn colour(&self, _world: &World<W, H, C>, _idx: Coord, _cell: C) -> Option<[u8; 4]> {
+		None
+	}
+}
+
+/// Adhesion energy, with the penalty between two different cells given by the
+/// matrix `j` over their types.
+pub struct AdhesionTerm {
+	pub j: AdhesionMatrix,
+}
+
+impl AdhesionTerm {
+	#[must_use]
+	pub fn new(j: AdhesionMatrix) -> Self {
+		Self { j }
+	}
+
+	/// Creates the term with the same `penalty` between any two different
+	/// cells.
+	#[must_use]
+	pub fn uniform(penalty: f32) -> Self {
+		Self::new(AdhesionMatrix::uniform(penalty))
+	}
+
+	fn penalty<C: CPMCell>(&self, a: C, b: C) -> f32 {
+		self.j.get(a.cell_type(), b.cell_type())
+	}
+}
+
+impl<const W: usize, const H: usize, C: CPMCell> Term<W, H, C> for AdhesionTerm {
+	fn name(&self) -> &'static str {
+		"adhesion"
+	}
+
+	fn energy(&self, world: &World<W, H, C>) -> Option<f64> {
+		Some(adhesion_energy(world, |a, b| self.penalty(a, b)))
+	}
+
+	fn delta(
+		&self,
+		world: &World<W, H, C>,
+		src: C,
+		dest: C,
+		_src_idx: Coord,
+		dest_idx: Coord,
+	) -> f32 {
+		let penalty = |a, b| self.penalty(a, b);
+		adhesion(world, dest_idx, src, penalty) - adhesion(world, dest_idx, dest, penalty)
+	}
+}
+
+/// Keeps the volume of every (non-background) cell close to the `target` of
+/// its type.
+pub struct VolumeTerm {
+	pub target: PerType<u32>,
+	pub lambda: PerType<f32>,
+	volumes: CellVolumes,
+
+	/// The IDs of dying cells, which have a target of zero.
+	dying: HashSet<usize>,
+}
+
+impl VolumeTerm {
+	#[must_use]
+	pub fn new<const W: usize, const H: usize, C: CPMCell>(
+		target: impl Into<PerType<u32>>,
+		lambda: impl Into<PerType<f32>>,
+		world: &World<W, H, C>,
+	) -> Self {
+		Self {
+			target: target.into(),
+			lambda: lambda.into(),
+			volumes: CellVolumes::from_world(world),
+			dying: HashSet::new(),
+		}
+	}
+
+	#[must_use]
+	pub fn volumes(&self) -> &CellVolumes {
+		&self.volumes
+	}
+
+	fn penalty<C: CPMCell>(&self, cell: C, volume: u32) -> f32 {
+		if cell.is_bg() {
+			0.0
+		} else {
+			let cell_type = cell.cell_type();
+			let target = if self.dying.contains(&cell.id()) {
+				0
+			} else {
+				self.target.get(cell_type)
+			};
+			self.lambda.get(cell_type) * (volume as f32 - target as f32).powi(2)
+		}
+	}
+}
+
+impl<const W: usize, const H: usize, C: CPMCell> Term<W, H, C> for VolumeTerm {
+	fn name(&self) -> &'static str {
+		"volume"
+	}
+
+	fn energy(&self, world: &World<W, H, C>) -> Option<f64> {
+		Some(volume_energy(world, |cell, volume| {
+			self.penalty(cell, volume)
+		}))
+	}
+
+	fn delta(
+		&self,
+		_world: &World<W, H, C>,
+		src: C,
+		dest: C,
+		_src_idx: Coord,
+		_dest_idx: Coord,
+	) -> f32 {
+		let src_vol = self.volumes.get(src);
+		let dest_vol = self.volumes.get(dest);
+		volume_delta(src, dest, src_vol, dest_vol, |cell, volume| {
+			self.penalty(cell, volume)
+		})
+	}
+
+	fn update(
+		&mut self,
+		world: &mut World<W, H, C>,
+		src: C,
+		dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) {
+		self.volumes.update(world, src, dest, src_idx, dest_idx);
+	}
+
+	fn verify(&self, world: &World<W, H, C>) -> Result<(), Divergence> {
+		self.volumes.verify(world)
+	}
+
+	fn on_apoptosis(&mut self, cell: C) {
+		self.dying.insert(cell.id());
+	}
+
+	fn on_removed(&mut self, cell: C) {
+		self.dying.remove(&cell.id());
+	}
+}
+
+/// Keeps the perimeter of every (non-background) cell close to the `target`
+/// of its type.
+pub struct PerimeterTerm {
+	pub target: PerType<u32>,
+	pub lambda: PerType<f32>,
+	perimeters: CellPerimeters,
+
+	/// The IDs of dying cells, which have a target of zero.
+	dying: HashSet<usize>,
+}
+
+impl PerimeterTerm {
+	#[must_use]
+	pub fn new<const W: usize, const H: usize, C: CPMCell>(
+		target: impl Into<PerType<u32>>,
+		lambda: impl Into<PerType<f32>>,
+		world: &World<W, H, C>,
+	) -> Self {
+		Self {
+			target: target.into(),
+			lambda: lambda.into(),
+			perimeters: CellPerimeters::from_world(world),
+			dying: HashSet::new(),
+		}
+	}
+
+	#[must_use]
+	pub fn perimeters(&self) -> &CellPerimeters {
+		&self.perimeters
+	}
+
+	fn penalty<C: CPMCell>(&self, cell: C, perimeter: u32) -> f32 {
+		if cell.is_bg() {
+			0.0
+		} else {
+			let cell_type = cell.cell_type();
+			let target = if self.dying.contains(&cell.id()) {
+				0
+			} else {
+				self.target.get(cell_type)
+			};
+			self.lambda.get(cell_type) * (perimeter as f32 - target as f32).powi(2)
+		}
+	}
+}
+
+impl<const W: usize, const H: usize, C: CPMCell> Term<W, H, C> for PerimeterTerm {
+	fn name(&self) -> &'static str {
+		"perimeter"
+	}
+
+	fn energy(&self, world: &World<W, H, C>) -> Option<f64> {
+		Some(perimeter_energy(world, |cell, perimeter| {
+			self.penalty(cell, perimeter)
+		}))
+	}
+
+	fn delta(
+		&self,
+		world: &World<W, H, C>,
+		src: C,
+		dest: C,
+		_src_idx: Coord,
+		dest_idx: Coord,
+	) -> f32 {
+		let src_perim = self.perimeters.get(src);
+		let dest_perim = self.perimeters.get(dest);
+		perimeter_delta(
+			world,
+			src,
+			dest,
+			dest_idx,
+			src_perim,
+			dest_perim,
+			|cell, perimeter| self.penalty(cell, perimeter),
+		)
+	}
+
+	fn update(
+		&mut self,
+		world: &mut World<W, H, C>,
+		src: C,
+		dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) {
+		self.perimeters.update(world, src, dest, src_idx, dest_idx);
+	}
+
+	fn verify(&self, world: &World<W, H, C>) -> Result<(), Divergence> {
+		self.perimeters.verify(world)
+	}
+
+	fn on_apoptosis(&mut self, cell: C) {
+		self.dying.insert(cell.id());
+	}
+
+	fn on_removed(&mut self, cell: C) {
+		self.dying.remove(&cell.id());
+	}
+}
+
+/// The Act model of cell migration: pixels that were recently added to a cell
+/// are active, and copies from active into less active regions are favoured.
+/// The activity is stored in a layer of the world and decreases by one every
+/// step, and pixels that change cell without a copy keep theirs. The
+/// parameters of the copying cell are used, or those of the target cell when
+/// the background copies.
+pub struct ActTerm {
+	pub lambda: PerType<f32>,
+	pub max_act: PerType<u8>,
+	layer: LayerId<u8>,
+}
+
+impl ActTerm {
+	/// Creates the term, and adds the act layer to `world` (with all
+	/// non-background pixels at the `max_act` of their type).
+	#[must_use]
+	pub fn new<const W: usize, const H: usize, C: CPMCell>(
+		lambda: impl Into<PerType<f32>>,
+		max_act: impl Into<PerType<u8>>,
+		world: &mut World<W, H, C>,
+	) -> Self {
+		let max_act = max_act.into();
+		let act = Layer::from_fn(|i| {
+			let cell = world.img.buf()[i];
+			if cell.is_bg() {
+				0
+			} else {
+				max_act.get(cell.cell_type())
+			}
+		});
+		Self {
+			lambda: lambda.into(),
+			max_act,
+			layer: world.layers.add(act),
+		}
+	}
+
+	/// Returns the layer of the world that holds the activity of every pixel.
+	#[must_use]
+	pub fn layer(&self) -> LayerId<u8> {
+		self.layer
+	}
+}
+
+impl<const W: usize, const H: usize, C: CPMCell> Term<W, H, C> for ActTerm {
+	fn name(&self) -> &'static str {
+		"act"
+	}
+
+	fn energy(&self, _world: &World<W, H, C>) -> Option<f64> {
+		None
+	}
+
+	fn delta(
+		&self,
+		world: &World<W, H, C>,
+		src: C,
+		dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) -> f32 {
+		let cell_type = if src.is_bg() { dest } else { src }.cell_type();
+		let max_act = self.max_act.get(cell_type);
+		if max_act == 0 {
+			return 0.0;
+		}
+		let activity_delta =
+			gm_act(world, self.layer, src_idx) - gm_act(world, self.layer, dest_idx);
+		-(self.lambda.get(cell_type) / f32::from(max_act)) * activity_delta
+	}
+
+	fn update(
+		&mut self,
+		world: &mut World<W, H, C>,
+		src: C,
+		_dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) {
+		let act = world.layers.get_mut(self.layer);
+		let new_act = if src.is_bg() || src_idx == dest_idx {
+			act.get(src_idx)
+		} else {
+			self.max_act.get(src.cell_type())
+		};
+		act.set(dest_idx, new_act);
+	}
+
+	fn after_step(&mut self, world: &mut World<W, H, C>) {
+		for act in world.layers.get_mut(self.layer).img.pixels_mut() {
+			*act = act.saturating_sub(1);
+		}
+	}
+
+	/// Shades active cells from black to red by their activity.
+	fn colour(&self, world: &World<W, H, C>, idx: Coord, cell: C) -> Option<[u8; 4]> {
+		let max_act = self.max_act.get(cell.cell_type());
+		if cell.is_bg() || max_act == 0 {
+			return None;
+		}
+		// The pixel may have kept the activity of a type with a higher maximum
+		let act = world.layers.get(self.layer).get(idx).min(max_act);
+		Some([act * (255 / max_act), 0x00, 0x00, 0xff])
+	}
+}
+
+/// Chemotaxis: copies up the gradient of a chemical are favoured by `lambda`
+/// (of the copying cell), or disfavoured if it is negative. Every step, each
+/// pixel secretes (or, if negative, consumes) the `secretion` of its type per
+/// unit of time, after which the chemical diffuses and decays for `dt`.
+pub struct ChemotaxisTerm {
+	pub lambda: PerType<f32>,
+	pub secretion: PerType<f32>,
+	pub diffusion: Diffusion,
+	pub dt: f32,
+	layer: LayerId<f32>,
+}
+
+impl ChemotaxisTerm {
+	/// Creates the term, and adds the chemical layer to `world` (with a
+	/// concentration of `initial` everywhere).
+	#[must_use]
+	pub fn new<const W: usize, const H: usize, C: CPMCell>(
+		lambda: impl Into<PerType<f32>>,
+		secretion: impl Into<PerType<f32>>,
+		diffusion: Diffusion,
+		dt: f32,
+		initial: f32,
+		world: &mut World<W, H, C>,
+	) -> Self {
+		Self {
+			lambda: lambda.into(),
+			secretion: secretion.into(),
+			diffusion,
+			dt,
+			layer: world.layers.add(Layer::new(initial)),
+		}
+	}
+
+	/// Returns the layer of the world that holds the concentration of the
+	/// chemical.
+	#[must_use]
+	pub fn layer(&self) -> LayerId<f32> {
+		self.layer
+	}
+}
+
+impl<const W: usize, const H: usize, C: CPMCell> Term<W, H, C> for ChemotaxisTerm {
+	fn name(&self) -> &'static str {
+		"chemotaxis"
+	}
+
+	fn energy(&self, _world: &World<W, H, C>) -> Option<f64> {
+		None
+	}
+
+	fn delta(
+		&self,
+		world: &World<W, H, C>,
+		src: C,
+		_dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) -> f32 {
+		let chemical = world.layers.get(self.layer);
+		-self.lambda.get(src.cell_type()) * (chemical.get(dest_idx) - chemical.get(src_idx))
+	}
+
+	fn after_step(&mut self, world: &mut World<W, H, C>) {
+		let chemical = world.layers.get_mut(self.layer);
+		for (concentration, cell) in chemical.img.pixels_mut().zip(world.img.pixels()) {
+			let secretion = self.secretion.get(cell.cell_type());
+			*concentration = (*concentration + secretion * self.dt).max(0.0);
+		}
+		self.diffusion.step(chemical, self.dt);
+	}
+}
+
+/// Penalises copies that would split a cell into pieces that are not
+/// 4-connected, or that would add a pixel that only touches its cell
+/// diagonally. With an infinite `penalty`, this is a hard constraint.
+pub struct ConnectivityTerm {
+	pub penalty: PerType<f32>,
+}
+
+impl ConnectivityTerm {
+	#[must_use]
+	pub fn new(penalty: impl Into<PerType<f32>>) -> Self {
+		Self {
+			penalty: penalty.into(),
+		}
+	}
+}
+
+impl<const W: usize, const H: usize, C: CPMCell> Term<W, H, C> for ConnectivityTerm {
+	fn name(&self) -> &'static str {
+		"connectivity"
+	}
+
+	fn energy(&self, _world: &World<W, H, C>) -> Option<f64> {
+		None
+	}
+
+	fn delta(
+		&self,
+		world: &World<W, H, C>,
+		src: C,
+		dest: C,
+		_src_idx: Coord,
+		dest_idx: Coord,
+	) -> f32 {
+		let mut delta = 0.0;
+		if !src.is_bg() && !touches(world, dest_idx, src) {
+			delta += self.penalty.get(src.cell_type());
+		}
+		if !dest.is_bg() && would_split(world, dest_idx) {
+			delta += self.penalty.get(dest.cell_type());
+		}
+		delta
+	}
+}
+
+/// Persistent random walks: every cell has a preferred direction of motion,
+/// and copies in that direction are favoured by `lambda`. After every step,
+/// the direction moves towards the displacement of the centroid of the cell,
+/// keeping a fraction `memory` of the old direction, and is then rotated by a
+/// random angle of at most `noise` radians.
+pub struct PersistenceTerm {
+	pub lambda: PerType<f32>,
+	pub memory: PerType<f32>,
+	pub noise: f32,
+
+	/// The unit direction of every cell, by ID.
+	directions: HashMap<usize, (f32, f32)>,
+
+	centroids: CellCentroids,
+
+	/// The type of every cell, for its `memory`.
+	types: Registry<usize>,
+
+	/// The centroid of every cell at the end of the previous step, by ID.
+	previous: HashMap<usize, (f32, f32)>,
+}
+
+impl PersistenceTerm {
+	/// Creates the term, with a random direction for every cell in `world`.
+	#[must_use]
+	pub fn new<const W: usize, const H: usize, C: CPMCell>(
+		lambda: impl Into<PerType<f32>>,
+		memory: impl Into<PerType<f32>>,
+		noise: f32,
+		world: &World<W, H, C>,
+	) -> Self {
+		let mut types = Registry::new();
+		for idx in world.positions(|c| !c.is_bg()) {
+			let cell = world.get_cell(idx);
+			*types.get_mut(cell.id()) = cell.cell_type();
+		}
+		let mut term = Self {
+			lambda: lambda.into(),
+			memory: memory.into(),
+			noise,
+			directions: HashMap::new(),
+			centroids: CellCentroids::from_world(world),
+			types,
+			previous: HashMap::new(),
+		};
+		term.update_directions::<W, H>();
+		term
+	}
+
+	/// Returns the preferred direction of `cell`, if it has one yet.
+	#[must_use]
+	pub fn direction<C: CPMCell>(&self, cell: C) -> Option<(f32, f32)> {
+		self.directions.get(&cell.id()).copied()
+	}
+
+	fn update_directions<const W: usize, const H: usize>(&mut self) {
+		let mut rng = rand::thread_rng();
+		let mut previous = HashMap::with_capacity(self.previous.len());
+		let mut directions = HashMap::with_capacity(self.directions.len());
+		for (id, current) in self.centroids.iter::<W, H>() {
+			let old = self.directions.get(&id).copied();
+			let angle = match (old, self.previous.get(&id)) {
+				(Some(old), Some(&previous)) => {
+					let (dx, dy) = centroid_displacement::<W, H>(previous, current);
+					let length = dx.hypot(dy);
+					let memory = self.memory.get(self.types.get(id));
+					// Without movement, or if the movement cancels out the old
+					// direction, keep the old direction
+					let (x, y) = (length > 0.0)
+						.then(|| {
+							(
+								memory * old.0 + (1.0 - memory) * dx / length,
+								memory * old.1 + (1.0 - memory) * dy / length,
+							)
+						})
+						.filter(|&(x, y)| x.hypot(y) > 0.0)
+						.unwrap_or(old);
+					let noise = if self.noise > 0.0 {
+						rng.gen_range(-self.noise..=self.noise)
+					} else {
+						0.0
+					};
+					y.atan2(x) + noise
+				}
+				_ => rng.gen_range(0.0..core::f32::consts::TAU),
+			};
+			let (sin, cos) = angle.sin_cos();
+			directions.insert(id, (cos, sin));
+			previous.insert(id, current);
+		}
+		self.directions = directions;
+		self.previous = previous;
+	}
+}
+
+impl<const W: usize, const H: usize, C: CPMCell> Term<W, H, C> for PersistenceTerm {
+	fn name(&self) -> &'static str {
+		"persistence"
+	}
+
+	fn energy(&self, _world: &World<W, H, C>) -> Option<f64> {
+		None
+	}
+
+	fn delta(
+		&self,
+		_world: &World<W, H, C>,
+		src: C,
+		_dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) -> f32 {
+		if src.is_bg() {
+			return 0.0;
+		}
+		let Some((x, y)) = self.direction(src) else {
+			return 0.0;
+		};
+		let (dx, dy) = World::<W, H, C>::displacement(src_idx, dest_idx);
+		let (dx, dy) = (dx as f32, dy as f32);
+		-self.lambda.get(src.cell_type()) * (dx * x + dy * y) / dx.hypot(dy)
+	}
+
+	fn update(
+		&mut self,
+		world: &mut World<W, H, C>,
+		src: C,
+		dest: C,
+		src_idx: Coord,
+		dest_idx: Coord,
+	) {
+		if !src.is_bg() {
+			*self.types.get_mut(src.id()) = src.cell_type();
+		}
+		self.centroids.update(world, src, dest, src_idx, dest_idx);
+	}
+
+	fn after_step(&mut self, _world: &mut World<W, H, C>) {
+		self.update_directions::<W, H>();
+	}
+}
@@ -0,0 +1,220 @@
+use std::{
+	collections::HashMap,
+	io::{self, Write},
+};
+
+use crate::cpm::centroids::{centroid_displacement, CellCentroids};
+
+/// The centroid of one cell after every MCS, unwrapped so that crossing the
+/// edges of the world does not cause jumps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+	pub id: usize,
+
+	/// The step of the first position.
+	pub start: usize,
+	pub positions: Vec<(f32, f32)>,
+}
+
+impl Track {
+	/// Returns the distance moved in every step.
+	#[must_use]
+	pub fn speeds(&self) -> Vec<f32> {
+		self.steps().map(|(dx, dy)| dx.hypot(dy)).collect()
+	}
+
+	/// Returns the mean squared displacement for every lag from 0 up to
+	/// `max_lag` steps, averaged over all pairs of positions that far apart.
+	/// Lags longer than the track are left out.
+	#[must_use]
+	pub fn mean_squared_displacement(&self, max_lag: usize) -> Vec<f32> {
+		let (sums, counts) = self.squared_displacements(max_lag);
+		sums.iter()
+			.zip(counts)
+			.map(|(sum, count)| sum / count as f32)
+			.collect()
+	}
+
+	/// Returns the mean cosine of the angle between the steps of the cell for
+	/// every lag from 0 up to `max_lag`. Steps without movement are skipped.
+	#[must_use]
+	pub fn direction_autocorrelation(&self, max_lag: usize) -> Vec<f32> {
+		let directions: Vec<Option<(f32, f32)>> = self
+			.steps()
+			.map(|(dx, dy)| {
+				let length = dx.hypot(dy);
+				(length > 0.0).then(|| (dx / length, dy / length))
+			})
+			.collect();
+		(0..=max_lag.min(directions.len().saturating_sub(1)))
+			.map(|lag| {
+				let (sum, count) = directions
+					.iter()
+					.zip(&directions[lag..])
+					.filter_map(|(a, b)| Some(((*a)?, (*b)?)))
+					.fold((0.0, 0), |(sum, count), (a, b)| {
+						(sum + a.0 * b.0 + a.1 * b.1, count + 1)
+					});
+				if count == 0 {
+					0.0
+				} else {
+					sum / count as f32
+				}
+			})
+			.collect()
+	}
+
+	/// Returns the persistence time in steps: the lag at which the
+	/// [direction autocorrelation](Track::direction_autocorrelation) drops
+	/// below 1/e, interpolated between steps. Returns [`None`] if it does not
+	/// drop that far within the track.
+	#[must_use]
+	pub fn persistence_time(&self) -> Option<f32> {
+		let threshold = (-1.0f32).exp();
+		let autocorrelation = self.direction_autocorrelation(self.positions.len());
+		if *autocorrelation.first()? < threshold {
+			return None;
+		}
+		autocorrelation
+			.windows(2)
+			.enumerate()
+			.find(|(_, pair)| pair[1] < threshold)
+			.map(|(lag, pair)| lag as f32 + (pair[0] - threshold) / (pair[0] - pair[1]))
+	}
+
+	/// Returns the distance between the first and last position divided by
+	/// the length of the path in between: 1 for a straight line and close to
+	/// 0 for a random walk. Returns [`None`] if the cell did not move.
+	#[must_use]
+	pub fn directionality_ratio(&self) -> Option<f32> {
+		let (first, last) = (self.positions.first()?, self.positions.last()?);
+		let path: f32 = self.speeds().iter().sum();
+		(path > 0.0).then(|| (last.0 - first.0).hypot(last.1 - first.1) / path)
+	}
+
+	fn steps(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+		self.positions
+			.windows(2)
+			.map(|pair| (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1))
+	}
+
+	/// Returns the sums and numbers of squared displacements per lag.
+	fn squared_displacements(&self, max_lag: usize) -> (Vec<f32>, Vec<usize>) {
+		let n_lags = max_lag.min(self.positions.len().saturating_sub(1)) + 1;
+		let mut sums = vec![0.0; n_lags];
+		let mut counts = vec![0; n_lags];
+		for (i, a) in self.positions.iter().enumerate() {
+			for (lag, b) in self.positions[i..].iter().take(n_lags).enumerate() {
+				sums[lag] += (b.0 - a.0).powi(2) + (b.1 - a.1).powi(2);
+				counts[lag] += 1;
+			}
+		}
+		(sums, counts)
+	}
+}
+
+/// Returns the mean squared displacement for every lag from 0 up to
+/// `max_lag`, averaged over all pairs of positions of all `tracks`.
+#[must_use]
+pub fn mean_squared_displacement<'a>(
+	tracks: impl IntoIterator<Item = &'a Track>,
+	max_lag: usize,
+) -> Vec<f32> {
+	let mut sums = vec![0.0; max_lag + 1];
+	let mut counts = vec![0; max_lag + 1];
+	for track in tracks {
+		let (track_sums, track_counts) = track.squared_displacements(max_lag);
+		for (lag, (sum, count)) in track_sums.into_iter().zip(track_counts).enumerate() {
+			sums[lag] += sum;
+			counts[lag] += count;
+		}
+	}
+	sums.iter()
+		.zip(counts)
+		.take_while(|&(_, count)| count > 0)
+		.map(|(sum, count)| sum / count as f32)
+		.collect()
+}
+
+/// Records the centroid of every cell after every MCS. A track ends when its
+/// cell disappears or its ID is released (see [`Trajectories::finish`]), so a
+/// reused ID starts a new track.
+#[derive(Debug, Clone, Default)]
+pub struct Trajectories {
+	step: usize,
+	finished: Vec<Track>,
+
+	/// The tracks of the cells that are still alive, with their last
+	/// (wrapped) centroid.
+	active: HashMap<usize, (Track, (f32, f32))>,
+}
+
+impl Trajectories {
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds the current centroids as the next step.
+	pub fn record<const W: usize, const H: usize>(&mut self, centroids: &CellCentroids) {
+		let mut active = HashMap::with_capacity(self.active.len());
+		for (id, centroid) in centroids.iter::<W, H>() {
+			let track = match self.active.remove(&id) {
+				Some((mut track, last)) => {
+					let (dx, dy) = centroid_displacement::<W, H>(last, centroid);
+					let next = track.positions.last().map(|&(x, y)| (x + dx, y + dy));
+					track.positions.extend(next);
+					track
+				}
+				None => Track {
+					id,
+					start: self.step,
+					positions: vec![centroid],
+				},
+			};
+			active.insert(id, (track, centroid));
+		}
+		self.finished
+			.extend(self.active.drain().map(|(_, (track, _))| track));
+		self.active = active;
+		self.step += 1;
+	}
+
+	/// Ends the track of the cell with `id`, so that the next cell with that
+	/// ID starts a new track even if it appears before the next step.
+	pub fn finish(&mut self, id: usize) {
+		if let Some((track, _)) = self.active.remove(&id) {
+			self.finished.push(track);
+		}
+	}
+
+	/// Returns the number of recorded steps.
+	#[must_use]
+	pub fn steps(&self) -> usize {
+		self.step
+	}
+
+	/// Returns all tracks, the finished ones first.
+	pub fn tracks(&self) -> impl Iterator<Item = &Track> {
+		let mut active: Vec<_> = self.active.values().map(|(track, _)| track).collect();
+		active.sort_unstable_by_key(|track| track.id);
+		self.finished.iter().chain(active)
+	}
+}
+
+/// Writes trajectories as CSV, with the columns `track,id,step,x,y`.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn write_trajectories_csv<'a, Wr: Write>(
+	out: &mut Wr,
+	tracks: impl IntoIterator<Item = &'a Track>,
+) -> io::Result<()> {
+	writeln!(out, "track,id,step,x,y")?;
+	for (i, track) in tracks.into_iter().enumerate() {
+		for (step, (x, y)) in (track.start..).zip(&track.positions) {
+			writeln!(out, "{i},{},{step},{x},{y}", track.id)?;
+		}
+	}
+	Ok(())
+}
@@ -1,5 +1,7 @@
+use std::collections::HashMap;
+
 use crate::{
-	cpm::CPM,
+	cpm::{CPMCell, CPM},
 	world::{Coord, World},
 };
 
@@ -25,10 +27,44 @@ pub trait Volume<const W: usize, const H: usize>: CPM<W, H> {
 	) -> f32 {
 		let src_vol = self.volume(world, src_idx, src);
 		let dest_vol = self.volume(world, dest_idx, dest);
-		let src_gain =
-			self.get_volume_penalty(src, src_vol + 1) - self.get_volume_penalty(src, src_vol);
-		let dest_loss = self.get_volume_penalty(dest, dest_vol.saturating_sub(1))
-			- self.get_volume_penalty(dest, dest_vol);
-		src_gain + dest_loss
+		volume_delta(src, dest, src_vol, dest_vol, |cell, volume| {
+			self.get_volume_penalty(cell, volume)
+		})
+	}
+
+	/// Returns the total volume energy of `world`.
+	#[must_use]
+	fn volume_energy(&self, world: &World<W, H, Self::C>) -> f64 {
+		volume_energy(world, |cell, volume| self.get_volume_penalty(cell, volume))
+	}
+}
+
+/// Returns the change in volume energy when `src` (with volume `src_vol`)
+/// gains a pixel from `dest` (with volume `dest_vol`).
+pub fn volume_delta<C: Copy, F>(src: C, dest: C, src_vol: u32, dest_vol: u32, penalty: F) -> f32
+where
+	F: Fn(C, u32) -> f32,
+{
+	let src_gain = penalty(src, src_vol + 1) - penalty(src, src_vol);
+	let dest_loss = penalty(dest, dest_vol.saturating_sub(1)) - penalty(dest, dest_vol);
+	src_gain + dest_loss
+}
+
+/// Returns the total volume energy of `world`: the sum of `penalty` over all
+/// non-background cells, with their volumes counted from scratch.
+pub fn volume_energy<const W: usize, const H: usize, C: CPMCell, F>(
+	world: &World<W, H, C>,
+	penalty: F,
+) -> f64
+where
+	F: Fn(C, u32) -> f32,
+{
+	let mut volumes = HashMap::new();
+	for cell in world.img.pixels().filter(|c| !c.is_bg()) {
+		volumes.entry(cell.id()).or_insert((cell, 0)).1 += 1;
 	}
+	volumes
+		.into_values()
+		.map(|(cell, volume)| f64::from(penalty(cell, volume)))
+		.sum()
 }
@@ -0,0 +1,221 @@
+//! The density (or majority) classification task: a binary automaton should
+//! converge to all ones if the initial condition contains mostly ones, and to
+//! all zeroes otherwise. See Mitchell, Crutchfield and Hraber, "Evolving
+//! cellular automata to perform computations" (1994).
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+	ca::{rule_1d::Rule1D, Automaton},
+	world::World,
+};
+
+/// How the initial conditions are generated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InitialDensity {
+	/// Every cell is one with probability `p`. With `p = 0.5` (the unbiased
+	/// distribution), most initial conditions have a density close to 1/2,
+	/// which makes them hard to classify. This is used to measure the
+	/// published performance of a rule.
+	Binomial(f64),
+
+	/// The number of ones is drawn uniformly from `0..=len`, so that the density
+	/// is uniformly distributed. This is used as fitness during evolution.
+	Uniform,
+}
+
+impl InitialDensity {
+	/// Returns a random initial condition of `len` cells.
+	#[must_use]
+	pub fn sample<R: Rng>(self, len: usize, rng: &mut R) -> Vec<bool> {
+		match self {
+			InitialDensity::Binomial(p) => (0..len).map(|_| rng.gen_bool(p)).collect(),
+			InitialDensity::Uniform => {
+				let n_ones = rng.gen_range(0..=len);
+				let mut cells: Vec<bool> = (0..len).map(|i| i < n_ones).collect();
+				cells.shuffle(rng);
+				cells
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DensityTask {
+	/// The maximum number of steps (M) the rule gets to converge.
+	pub steps: usize,
+
+	/// The number of initial conditions to test.
+	pub trials: usize,
+
+	pub initial: InitialDensity,
+}
+
+impl DensityTask {
+	/// The performance measure of Mitchell et al.: 10⁴ unbiased initial
+	/// conditions, and about 2N steps for lattices of N = 149 cells.
+	#[must_use]
+	pub fn performance() -> Self {
+		Self {
+			steps: 320,
+			trials: 10_000,
+			initial: InitialDensity::Binomial(0.5),
+		}
+	}
+
+	/// The fitness measure of Mitchell et al.: 100 initial conditions with
+	/// uniformly distributed densities.
+	#[must_use]
+	pub fn fitness() -> Self {
+		Self {
+			steps: 320,
+			trials: 100,
+			initial: InitialDensity::Uniform,
+		}
+	}
+
+	/// Returns the fraction of initial conditions that `rule` classifies
+	/// correctly. Every row of the world is a separate lattice of `W` cells, so
+	/// `H` initial conditions are tested at once. Initial conditions with
+	/// exactly as many ones as zeroes (only possible for even `W`) have no
+	/// correct answer and are not counted.
+	#[must_use]
+	pub fn score_1d<const W: usize, const H: usize>(&self, rule: &Rule1D, seed: u64) -> f32 {
+		let mut rng = StdRng::seed_from_u64(seed);
+		let mut correct = 0;
+		let mut counted = 0;
+
+		for batch in 0..self.trials.div_ceil(H) {
+			let rows = (self.trials - batch * H).min(H);
+			let mut world = World::<W, H, bool>::default();
+			let mut majorities = Vec::with_capacity(rows);
+			for row in world.img.rows_mut().take(rows) {
+				row.copy_from_slice(&self.initial.sample(W, &mut rng));
+				majorities.push(majority(row));
+			}
+
+			for _ in 0..self.steps {
+				Automaton::<W, H>::step(rule, &mut world);
+			}
+
+			for (row, expected) in world.img.rows().zip(majorities) {
+				if let Some(expected) = expected {
+					counted += 1;
+					correct += u32::from(uniform(row) == Some(expected));
+				}
+			}
+		}
+
+		correct as f32 / counted.max(1) as f32
+	}
+
+	/// Returns the fraction of initial conditions that a two-dimensional
+	/// `automaton` classifies correctly, using the interior of the world (see
+	/// [`World::interior_rows`]) as a single lattice. Like
+	/// [`DensityTask::score_1d`], ties are not counted.
+	#[must_use]
+	pub fn score_2d<const W: usize, const H: usize, A>(&self, automaton: &A, seed: u64) -> f32
+	where
+		A: Automaton<W, H, C = bool>,
+	{
+		let mut rng = StdRng::seed_from_u64(seed);
+		let mut correct = 0;
+		let mut counted = 0;
+
+		for _ in 0..self.trials {
+			let cells = self.initial.sample((W - 2) * (H - 2), &mut rng);
+			let Some(expected) = majority(&cells) else {
+				continue;
+			};
+			let mut world = World::<W, H, bool>::default();
+			for (row, cells) in world.img.rows_mut().skip(1).zip(cells.chunks_exact(W - 2)) {
+				row[1..W - 1].copy_from_slice(cells);
+			}
+
+			counted += 1;
+			correct += u32::from(classify(automaton, &mut world, self.steps) == Some(expected));
+		}
+
+		correct as f32 / counted.max(1) as f32
+	}
+}
+
+/// Runs `automaton` for at most `max_steps` steps, and returns the state of
+/// the cells in the interior of the world once they are all equal, or
+/// [`None`] if they never are.
+pub fn classify<const W: usize, const H: usize, A>(
+	automaton: &A,
+	world: &mut World<W, H, bool>,
+	max_steps: usize,
+) -> Option<bool>
+where
+	A: Automaton<W, H, C = bool>,
+{
+	for _ in 0..max_steps {
+		if let Some(state) = uniform_interior(world) {
+			return Some(state);
+		}
+		automaton.step(world);
+	}
+	uniform_interior(world)
+}
+
+/// Returns whether most cells are one, or [`None`] for a tie.
+#[must_use]
+pub fn majority(cells: &[bool]) -> Option<bool> {
+	let n_ones = cells.iter().filter(|&&cell| cell).count();
+	match (2 * n_ones).cmp(&cells.len()) {
+		core::cmp::Ordering::Less => Some(false),
+		core::cmp::Ordering::Equal => None,
+		core::cmp::Ordering::Greater => Some(true),
+	}
+}
+
+/// Returns the state of the cells if they are all equal.
+#[must_use]
+fn uniform(cells: &[bool]) -> Option<bool> {
+	let first = *cells.first()?;
+	cells.iter().all(|&cell| cell == first).then_some(first)
+}
+
+/// Returns the state of the cells in the interior of `world` if they are all
+/// equal.
+#[must_use]
+fn uniform_interior<const W: usize, const H: usize>(world: &World<W, H, bool>) -> Option<bool> {
+	let mut cells = world.interior_rows().flatten();
+	let first = *cells.next()?;
+	cells.all(|&cell| cell == first).then_some(first)
+}
+
+/// The Gács–Kurdyumov–Levin rule (radius 3), the classic hand-designed
+/// baseline for the density task: a zero cell takes the majority of itself and
+/// the cells 1 and 3 to its left, a one cell takes the majority of itself and
+/// the cells 1 and 3 to its right. Its performance is about 0.81 for N = 149.
+#[must_use]
+pub fn gkl() -> Rule1D {
+	let radius = 3;
+	let table = (0..Rule1D::table_len(radius))
+		.map(|idx| {
+			// Bit 6 is the leftmost cell (offset -3), bit 0 the rightmost (+3)
+			let cell = |offset: i32| idx & (1 << (3 - offset)) != 0;
+			let votes = if cell(0) {
+				[cell(0), cell(1), cell(3)]
+			} else {
+				[cell(0), cell(-1), cell(-3)]
+			};
+			votes.iter().filter(|&&vote| vote).count() >= 2
+		})
+		.collect();
+	Rule1D::new(radius, table)
+}
+
+/// The naive local majority rule of the given `radius`, which gets stuck in
+/// mixed stable patterns and so performs poorly on the density task.
+#[must_use]
+pub fn local_majority(radius: usize) -> Rule1D {
+	let window = 2 * radius + 1;
+	let table = (0..Rule1D::table_len(radius))
+		.map(|idx: usize| idx.count_ones() as usize * 2 > window)
+		.collect();
+	Rule1D::new(radius, table)
+}
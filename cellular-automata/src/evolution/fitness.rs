@@ -0,0 +1,60 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{ca::Automaton, world::World};
+
+/// Returns a random binary world where every cell in the interior (see
+/// [`World::interior_rows`]) is alive with probability `density`.
+#[must_use]
+pub fn random_world<const W: usize, const H: usize, R: Rng>(
+	density: f64,
+	rng: &mut R,
+) -> World<W, H, bool> {
+	let mut world = World::default();
+	for row in world.img.rows_mut().skip(1).take(H - 2) {
+		for cell in &mut row[1..W - 1] {
+			*cell = rng.gen_bool(density);
+		}
+	}
+	world
+}
+
+/// Runs `automaton` for `steps` steps on `trials` random worlds with the given
+/// initial `density`, and returns the mean `score` of the final worlds. The
+/// worlds only depend on `seed`, so that different rules are compared on the
+/// same initial conditions. Can be used as the building block of a
+/// [`Fitness`](crate::evolution::Fitness) function.
+pub fn mean_score<const W: usize, const H: usize, A, S>(
+	automaton: &A,
+	seed: u64,
+	trials: usize,
+	density: f64,
+	steps: usize,
+	score: S,
+) -> f32
+where
+	A: Automaton<W, H, C = bool>,
+	S: Fn(&World<W, H, bool>) -> f32,
+{
+	let mut rng = StdRng::seed_from_u64(seed);
+	let total: f32 = (0..trials)
+		.map(|_| {
+			let mut world = random_world(density, &mut rng);
+			for _ in 0..steps {
+				automaton.step(&mut world);
+			}
+			score(&world)
+		})
+		.sum();
+	total / trials.max(1) as f32
+}
+
+/// Returns the fraction of live cells in the interior of the world.
+#[must_use]
+pub fn density<const W: usize, const H: usize>(world: &World<W, H, bool>) -> f32 {
+	let alive = world
+		.interior_rows()
+		.flatten()
+		.filter(|&&cell| cell)
+		.count();
+	alive as f32 / ((W - 2) * (H - 2)) as f32
+}
@@ -0,0 +1,52 @@
+use rand::Rng;
+
+use crate::{
+	ca::{life_like::LifeLike, rule_1d::Rule1D},
+	evolution::Genome,
+};
+
+impl Genome for LifeLike {
+	fn crossover<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+		let mut genes = [self.birth, self.survive].concat();
+		let other_genes = [other.birth, other.survive].concat();
+		crossover_bits(&mut genes, &other_genes, rng);
+
+		let mut child = Self::default();
+		child.birth.copy_from_slice(&genes[..9]);
+		child.survive.copy_from_slice(&genes[9..]);
+		child
+	}
+
+	fn mutate<R: Rng>(&mut self, rate: f32, rng: &mut R) {
+		mutate_bits(&mut self.birth, rate, rng);
+		mutate_bits(&mut self.survive, rate, rng);
+	}
+}
+
+impl Genome for Rule1D {
+	fn crossover<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+		let mut child = self.clone();
+		crossover_bits(child.table_mut(), other.table(), rng);
+		child
+	}
+
+	fn mutate<R: Rng>(&mut self, rate: f32, rng: &mut R) {
+		mutate_bits(self.table_mut(), rate, rng);
+	}
+}
+
+/// Single-point crossover: replaces the genes of `genes` after a random point
+/// with those of `other`.
+fn crossover_bits<R: Rng>(genes: &mut [bool], other: &[bool], rng: &mut R) {
+	let point = rng.gen_range(0..=genes.len());
+	genes[point..].copy_from_slice(&other[point..]);
+}
+
+/// Flips every gene with probability `rate`.
+fn mutate_bits<R: Rng>(genes: &mut [bool], rate: f32, rng: &mut R) {
+	for gene in genes {
+		if rng.gen::<f32>() < rate {
+			*gene = !*gene;
+		}
+	}
+}
@@ -0,0 +1,231 @@
+pub mod density;
+pub mod fitness;
+pub mod genome;
+
+use std::{
+	io::{self, Write},
+	num::NonZeroUsize,
+	thread,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// A candidate solution that can be evolved by a [`GeneticAlgorithm`].
+pub trait Genome: Clone + Send + Sync {
+	/// Returns a child combining the genes of `self` and `other`.
+	#[must_use]
+	fn crossover<R: Rng>(&self, other: &Self, rng: &mut R) -> Self;
+
+	/// Mutates every gene with probability `rate`.
+	fn mutate<R: Rng>(&mut self, rate: f32, rng: &mut R);
+}
+
+/// Scores a [`Genome`], higher is better. Evaluations of the same generation
+/// receive the same `seed`, so that every genome can be tested on the same
+/// (random) cases.
+pub trait Fitness<G>: Sync {
+	#[must_use]
+	fn fitness(&self, genome: &G, seed: u64) -> f32;
+}
+
+impl<G, F: Fn(&G, u64) -> f32 + Sync> Fitness<G> for F {
+	fn fitness(&self, genome: &G, seed: u64) -> f32 {
+		self(genome, seed)
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Params {
+	/// The number of best genomes that are copied unchanged into the next
+	/// generation.
+	pub elitism: usize,
+
+	/// The number of genomes competing in each tournament selection.
+	pub tournament_size: usize,
+
+	/// The probability that two parents are crossed over, instead of the first
+	/// parent being copied.
+	pub crossover_rate: f32,
+
+	/// The probability that a single gene is mutated.
+	pub mutation_rate: f32,
+
+	/// The number of threads used to evaluate the population.
+	pub threads: NonZeroUsize,
+}
+
+impl Default for Params {
+	fn default() -> Self {
+		Self {
+			elitism: 2,
+			tournament_size: 3,
+			crossover_rate: 0.9,
+			mutation_rate: 0.02,
+			threads: thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+		}
+	}
+}
+
+/// Fitness statistics of a single generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationStats {
+	pub generation: usize,
+	pub best: f32,
+	pub mean: f32,
+	pub worst: f32,
+}
+
+pub struct GeneticAlgorithm<G: Genome, F: Fitness<G>> {
+	params: Params,
+	fitness: F,
+	population: Vec<G>,
+	rng: StdRng,
+
+	/// The best genome of the last evaluated generation and its fitness.
+	best: Option<(G, f32)>,
+	log: Vec<GenerationStats>,
+}
+
+impl<G: Genome, F: Fitness<G>> GeneticAlgorithm<G, F> {
+	/// Creates a [`GeneticAlgorithm`] starting from the given population. The
+	/// size of the population stays the same in every generation.
+	#[must_use]
+	pub fn new(params: Params, fitness: F, population: Vec<G>, seed: u64) -> Self {
+		Self {
+			params,
+			fitness,
+			population,
+			rng: StdRng::seed_from_u64(seed),
+			best: None,
+			log: Vec::new(),
+		}
+	}
+
+	/// Returns the statistics of every generation so far.
+	#[must_use]
+	pub fn log(&self) -> &[GenerationStats] {
+		&self.log
+	}
+
+	/// Returns the best genome of the last evaluated generation, and its
+	/// fitness.
+	#[must_use]
+	pub fn best(&self) -> Option<&(G, f32)> {
+		self.best.as_ref()
+	}
+
+	#[must_use]
+	pub fn population(&self) -> &[G] {
+		&self.population
+	}
+
+	/// Runs `generations` generations, calling `on_generation` after each one.
+	pub fn run<L>(&mut self, generations: usize, mut on_generation: L)
+	where
+		L: FnMut(&GenerationStats),
+	{
+		for _ in 0..generations {
+			let stats = self.step();
+			on_generation(&stats);
+		}
+	}
+
+	/// Evaluates the current population and replaces it by the next generation.
+	pub fn step(&mut self) -> GenerationStats {
+		let seed = self.rng.gen();
+		let fitnesses = self.evaluate(seed);
+
+		let mut ranked: Vec<(G, f32)> = self.population.drain(..).zip(fitnesses).collect();
+		ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+		let stats = GenerationStats {
+			generation: self.log.len(),
+			best: ranked.first().map_or(f32::NAN, |(_, f)| *f),
+			mean: ranked.iter().map(|(_, f)| f).sum::<f32>() / ranked.len() as f32,
+			worst: ranked.last().map_or(f32::NAN, |(_, f)| *f),
+		};
+		self.log.push(stats);
+
+		self.population = self.next_generation(&ranked);
+		self.best = ranked.into_iter().next();
+		stats
+	}
+
+	/// Returns the fitness of every genome in the population, evaluated on
+	/// `params.threads` threads.
+	fn evaluate(&self, seed: u64) -> Vec<f32> {
+		let chunk_size = self
+			.population
+			.len()
+			.div_ceil(self.params.threads.get())
+			.max(1);
+		thread::scope(|scope| {
+			let handles: Vec<_> = self
+				.population
+				.chunks(chunk_size)
+				.map(|chunk| {
+					scope.spawn(move || {
+						chunk
+							.iter()
+							.map(|genome| self.fitness.fitness(genome, seed))
+							.collect::<Vec<f32>>()
+					})
+				})
+				.collect();
+			handles
+				.into_iter()
+				.flat_map(|handle| handle.join().expect("fitness evaluation panicked"))
+				.collect()
+		})
+	}
+
+	/// Creates the next generation from the population `ranked` from best to
+	/// worst, using elitism, tournament selection, crossover and mutation.
+	fn next_generation(&mut self, ranked: &[(G, f32)]) -> Vec<G> {
+		let size = ranked.len();
+		let mut next: Vec<G> = ranked
+			.iter()
+			.take(self.params.elitism)
+			.map(|(genome, _)| genome.clone())
+			.collect();
+
+		while next.len() < size {
+			let a = self.tournament(ranked);
+			let mut child = if self.rng.gen::<f32>() < self.params.crossover_rate {
+				let b = self.tournament(ranked);
+				ranked[a].0.crossover(&ranked[b].0, &mut self.rng)
+			} else {
+				ranked[a].0.clone()
+			};
+			child.mutate(self.params.mutation_rate, &mut self.rng);
+			next.push(child);
+		}
+		next
+	}
+
+	/// Returns the index of the winner of a tournament between random genomes.
+	/// Because `ranked` is sorted from best to worst, this is the lowest index.
+	fn tournament(&mut self, ranked: &[(G, f32)]) -> usize {
+		(0..self.params.tournament_size.max(1))
+			.map(|_| self.rng.gen_range(0..ranked.len()))
+			.min()
+			.unwrap_or_default()
+	}
+}
+
+/// Writes the statistics of every generation as CSV, with the columns
+/// `generation,best,mean,worst`.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn write_log_csv<Wr: Write>(out: &mut Wr, log: &[GenerationStats]) -> io::Result<()> {
+	writeln!(out, "generation,best,mean,worst")?;
+	for stats in log {
+		writeln!(
+			out,
+			"{},{},{},{}",
+			stats.generation, stats.best, stats.mean, stats.worst
+		)?;
+	}
+	Ok(())
+}
@@ -0,0 +1,32 @@
+use rand::Rng;
+
+use crate::{
+	ca::{
+		life_like::LifeLike,
+		sir::{Sir, State},
+	},
+	graph::GraphAutomaton,
+};
+
+impl GraphAutomaton for Sir {
+	type C = State;
+
+	fn graph_rule<R: Rng>(&self, cell: State, neighbours: &[State], rng: &mut R) -> State {
+		let n_infected = neighbours
+			.iter()
+			.filter(|&&neighbour| neighbour == State::Infected)
+			.count();
+		self.next(cell, n_infected, rng)
+	}
+}
+
+impl GraphAutomaton for LifeLike {
+	type C = bool;
+
+	/// Applies the rule to the number of live neighbours. Since the rule only
+	/// covers up to 8 neighbours, larger numbers are treated as 8.
+	fn graph_rule<R: Rng>(&self, cell: bool, neighbours: &[bool], _rng: &mut R) -> bool {
+		let n_alive = neighbours.iter().filter(|&&neighbour| neighbour).count();
+		self.next(cell, n_alive.min(8))
+	}
+}
@@ -0,0 +1,99 @@
+//! Random graph models.
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::graph::Graph;
+
+/// Returns an Erdős–Rényi random graph `G(n, p)`: every pair of the `n` nodes
+/// is connected with probability `p`.
+#[must_use]
+pub fn erdos_renyi<R: Rng>(n: usize, p: f64, rng: &mut R) -> Graph {
+	let mut graph = Graph::new(n);
+	for a in 0..n {
+		for b in a + 1..n {
+			if rng.gen_bool(p) {
+				graph.add_edge(a, b);
+			}
+		}
+	}
+	graph
+}
+
+/// Returns a ring of `n` nodes, each connected to its `k / 2` nearest
+/// neighbours on either side.
+#[must_use]
+pub fn ring_lattice(n: usize, k: usize) -> Graph {
+	let mut graph = Graph::new(n);
+	for a in 0..n {
+		for offset in 1..=k / 2 {
+			graph.add_edge(a, (a + offset) % n);
+		}
+	}
+	graph
+}
+
+/// Returns a Watts–Strogatz small-world graph: a [`ring_lattice`] of `n`
+/// nodes with degree `k`, where the far end of every edge is rewired to a
+/// random node with probability `beta`. For small `beta`, this keeps the high
+/// clustering of the lattice but has short paths like a random graph.
+#[must_use]
+pub fn watts_strogatz<R: Rng>(n: usize, k: usize, beta: f64, rng: &mut R) -> Graph {
+	let mut graph = ring_lattice(n, k);
+	for offset in 1..=k / 2 {
+		for a in 0..n {
+			let b = (a + offset) % n;
+			// A node connected to all others cannot be rewired
+			if !rng.gen_bool(beta) || graph.degree(a) >= n - 1 || !graph.has_edge(a, b) {
+				continue;
+			}
+			let new = loop {
+				let new = rng.gen_range(0..n);
+				if new != a && !graph.has_edge(a, new) {
+					break new;
+				}
+			};
+			graph.remove_edge(a, b);
+			graph.add_edge(a, new);
+		}
+	}
+	graph
+}
+
+/// Returns a Barabási–Albert scale-free graph of `n` nodes: starting from a
+/// complete graph of `m + 1` nodes, every new node connects to `m` existing
+/// nodes, chosen with probability proportional to their degree (preferential
+/// attachment). The degree distribution follows a power law `P(k) ~ k^-3`.
+///
+/// # Panics
+/// Panics if `m` is 0.
+#[must_use]
+pub fn barabasi_albert<R: Rng>(n: usize, m: usize, rng: &mut R) -> Graph {
+	assert!(m > 0, "new nodes must connect to at least one node");
+	let mut graph = Graph::new(n);
+	let initial = (m + 1).min(n);
+
+	// Every node appears once for each of its edges, so a uniform choice from
+	// this list is proportional to degree
+	let mut endpoints = Vec::new();
+	for a in 0..initial {
+		for b in a + 1..initial {
+			graph.add_edge(a, b);
+			endpoints.extend([a, b]);
+		}
+	}
+
+	for new in initial..n {
+		let mut targets = Vec::with_capacity(m);
+		while targets.len() < m {
+			let &target = endpoints.choose(rng).unwrap_or(&0);
+			if !targets.contains(&target) {
+				targets.push(target);
+			}
+		}
+		for target in targets {
+			graph.add_edge(new, target);
+			endpoints.extend([new, target]);
+		}
+	}
+	graph
+}
@@ -0,0 +1,285 @@
+//! Automata on arbitrary undirected graphs, where every node is a cell and its
+//! neighbours are the nodes it shares an edge with.
+
+pub mod automata;
+pub mod generators;
+
+use std::{collections::VecDeque, fs, path::Path};
+
+use rand::Rng;
+
+use crate::{load::Error, world::World, Cell};
+
+/// A simple undirected graph (without self-loops or multiple edges), stored
+/// as adjacency lists.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Graph {
+	adjacency: Vec<Vec<usize>>,
+}
+
+impl Graph {
+	/// Creates a graph of `n_nodes` nodes without edges.
+	#[must_use]
+	pub fn new(n_nodes: usize) -> Self {
+		Self {
+			adjacency: vec![Vec::new(); n_nodes],
+		}
+	}
+
+	/// Creates the graph of a `W`×`H` [`World`]: node `y * W + x` is connected
+	/// to its 8 neighbours, wrapping around the edges of the whole world. Note
+	/// that [`World::convolve`] instead wraps its interior through the
+	/// outermost rows and columns, so automata on this graph do not run
+	/// exactly like on the world.
+	#[must_use]
+	pub fn lattice<const W: usize, const H: usize>() -> Self {
+		let mut graph = Self::new(W * H);
+		for y in 0..H as u32 {
+			for x in 0..W as u32 {
+				for offset in [(1, -1), (1, 0), (1, 1), (0, 1)] {
+					let (nx, ny) = World::<W, H, bool>::get_neighbour_idx((x, y), offset);
+					graph.add_edge(y as usize * W + x as usize, ny as usize * W + nx as usize);
+				}
+			}
+		}
+		graph
+	}
+
+	/// Parses an edge list: one edge per line, given as two node indices
+	/// separated by whitespace. Empty lines and lines starting with `#` or `%`
+	/// are ignored. The graph has as many nodes as the largest index plus one.
+	///
+	/// # Errors
+	/// Returns an error if a line does not contain two node indices.
+	pub fn from_edge_list(text: &str) -> Result<Self, Error> {
+		let mut graph = Self::default();
+		for (line_nr, line) in text.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with(['#', '%']) {
+				continue;
+			}
+			let err = || Error::InvalidEdge(line.to_owned(), line_nr + 1);
+			let mut nodes = line.split_whitespace().map(str::parse::<usize>);
+			let (Some(Ok(a)), Some(Ok(b))) = (nodes.next(), nodes.next()) else {
+				return Err(err());
+			};
+			let n_nodes = a.max(b) + 1;
+			if n_nodes > graph.n_nodes() {
+				graph.adjacency.resize(n_nodes, Vec::new());
+			}
+			graph.add_edge(a, b);
+		}
+		Ok(graph)
+	}
+
+	/// Reads an edge list from a file, see [`Graph::from_edge_list`].
+	///
+	/// # Errors
+	/// Returns an error if the file cannot be read or is not a valid edge
+	/// list.
+	pub fn load_edge_list<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+		Self::from_edge_list(&fs::read_to_string(path)?)
+	}
+
+	#[must_use]
+	pub fn n_nodes(&self) -> usize {
+		self.adjacency.len()
+	}
+
+	#[must_use]
+	pub fn n_edges(&self) -> usize {
+		self.adjacency.iter().map(Vec::len).sum::<usize>() / 2
+	}
+
+	/// Adds an edge between `a` and `b`. Returns `false` (and does nothing) if
+	/// `a == b` or the edge already exists.
+	///
+	/// # Panics
+	/// Panics if `a` or `b` is not a node of the graph.
+	pub fn add_edge(&mut self, a: usize, b: usize) -> bool {
+		if a == b || self.has_edge(a, b) {
+			return false;
+		}
+		self.adjacency[a].push(b);
+		self.adjacency[b].push(a);
+		true
+	}
+
+	/// Removes the edge between `a` and `b`. Returns whether it existed.
+	pub fn remove_edge(&mut self, a: usize, b: usize) -> bool {
+		let Some(i) = self.adjacency[a].iter().position(|&n| n == b) else {
+			return false;
+		};
+		self.adjacency[a].swap_remove(i);
+		self.adjacency[b].retain(|&n| n != a);
+		true
+	}
+
+	#[must_use]
+	pub fn has_edge(&self, a: usize, b: usize) -> bool {
+		self.adjacency[a].contains(&b)
+	}
+
+	#[must_use]
+	pub fn neighbours(&self, node: usize) -> &[usize] {
+		&self.adjacency[node]
+	}
+
+	#[must_use]
+	pub fn degree(&self, node: usize) -> usize {
+		self.adjacency[node].len()
+	}
+
+	#[must_use]
+	pub fn mean_degree(&self) -> f32 {
+		2.0 * self.n_edges() as f32 / self.n_nodes().max(1) as f32
+	}
+
+	/// Returns the number of nodes of every degree, indexed by degree.
+	#[must_use]
+	pub fn degree_distribution(&self) -> Vec<usize> {
+		let max_degree = self.adjacency.iter().map(Vec::len).max().unwrap_or(0);
+		let mut counts = vec![0; max_degree + 1];
+		for neighbours in &self.adjacency {
+			counts[neighbours.len()] += 1;
+		}
+		counts
+	}
+
+	/// Returns the average local clustering coefficient: for every node, the
+	/// fraction of pairs of its neighbours that are connected themselves.
+	/// Nodes with fewer than two neighbours count as 0.
+	#[must_use]
+	pub fn clustering_coefficient(&self) -> f32 {
+		let total: f32 = (0..self.n_nodes())
+			.map(|node| {
+				let neighbours = self.neighbours(node);
+				let k = neighbours.len();
+				if k < 2 {
+					return 0.0;
+				}
+				let mut links = 0;
+				for (i, &a) in neighbours.iter().enumerate() {
+					for &b in &neighbours[i + 1..] {
+						links += usize::from(self.has_edge(a, b));
+					}
+				}
+				2.0 * links as f32 / (k * (k - 1)) as f32
+			})
+			.sum();
+		total / self.n_nodes().max(1) as f32
+	}
+
+	/// Returns the shortest path length from `source` to every node, or
+	/// [`None`] for nodes that cannot be reached.
+	#[must_use]
+	pub fn distances_from(&self, source: usize) -> Vec<Option<usize>> {
+		let mut distances = vec![None; self.n_nodes()];
+		distances[source] = Some(0);
+		let mut queue = VecDeque::from([source]);
+		while let Some(node) = queue.pop_front() {
+			let distance = distances[node].unwrap_or_default() + 1;
+			for &neighbour in self.neighbours(node) {
+				if distances[neighbour].is_none() {
+					distances[neighbour] = Some(distance);
+					queue.push_back(neighbour);
+				}
+			}
+		}
+		distances
+	}
+
+	/// Returns the mean shortest path length between all pairs of nodes that
+	/// are connected by a path. This takes a breadth-first search from every
+	/// node, so it is slow for large graphs.
+	#[must_use]
+	pub fn average_path_length(&self) -> f32 {
+		let (mut total, mut count) = (0, 0);
+		for source in 0..self.n_nodes() {
+			for distance in self.distances_from(source).into_iter().flatten() {
+				if distance > 0 {
+					total += distance;
+					count += 1;
+				}
+			}
+		}
+		total as f32 / count.max(1) as f32
+	}
+}
+
+/// The cells of a [`Graph`], one per node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphWorld<C: Cell> {
+	pub graph: Graph,
+	pub cells: Vec<C>,
+}
+
+impl<C: Cell> GraphWorld<C> {
+	#[must_use]
+	pub fn from_fn<F>(graph: Graph, function: F) -> Self
+	where
+		F: FnMut(usize) -> C,
+	{
+		let cells = (0..graph.n_nodes()).map(function).collect();
+		Self { graph, cells }
+	}
+
+	/// Creates a [`GraphWorld`] with the same cells as `world`, on the graph
+	/// returned by [`Graph::lattice`].
+	#[must_use]
+	pub fn from_world<const W: usize, const H: usize>(world: &World<W, H, C>) -> Self {
+		Self {
+			graph: Graph::lattice::<W, H>(),
+			cells: world.img.pixels().collect(),
+		}
+	}
+
+	/// Returns the states of the neighbours of `node`.
+	#[must_use]
+	pub fn neighbours(&self, node: usize) -> Vec<C> {
+		self.graph
+			.neighbours(node)
+			.iter()
+			.map(|&neighbour| self.cells[neighbour])
+			.collect()
+	}
+
+	/// Returns the fraction of cells for which `filter` returns `true`.
+	#[must_use]
+	pub fn fraction<F>(&self, filter: F) -> f32
+	where
+		F: Fn(C) -> bool,
+	{
+		let count = self.cells.iter().filter(|&&cell| filter(cell)).count();
+		count as f32 / self.cells.len().max(1) as f32
+	}
+}
+
+/// An automaton on a [`GraphWorld`], where the number of neighbours can
+/// differ between cells.
+pub trait GraphAutomaton {
+	type C: Cell;
+
+	/// Returns the next state of `cell`, given the states of its neighbours.
+	#[must_use]
+	fn graph_rule<R: Rng>(&self, cell: Self::C, neighbours: &[Self::C], rng: &mut R) -> Self::C;
+
+	/// Updates all cells synchronously.
+	fn graph_step<R: Rng>(&self, world: &mut GraphWorld<Self::C>, rng: &mut R) {
+		let mut neighbours = Vec::new();
+		let next = (0..world.cells.len())
+			.map(|node| {
+				neighbours.clear();
+				neighbours.extend(
+					world
+						.graph
+						.neighbours(node)
+						.iter()
+						.map(|&neighbour| world.cells[neighbour]),
+				);
+				self.graph_rule(world.cells[node], &neighbours, rng)
+			})
+			.collect();
+		world.cells = next;
+	}
+}
@@ -0,0 +1,166 @@
+use core::{any::Any, marker::PhantomData};
+
+use imgref::Img;
+
+use crate::world::Coord;
+
+/// A grid of values of the same size as a [`World`](crate::world::World), for
+/// per-pixel quantities that are not part of the cell state itself (such as a
+/// chemical concentration or an obstacle mask).
+#[derive(Clone)]
+pub struct Layer<const W: usize, const H: usize, T> {
+	pub img: Img<Vec<T>>,
+}
+
+impl<const W: usize, const H: usize, T: Copy> Layer<W, H, T> {
+	/// Creates a [`Layer`] where every pixel has the same `value`.
+	#[must_use]
+	pub fn new(value: T) -> Self {
+		Self {
+			img: Img::new(vec![value; W * H], W, H),
+		}
+	}
+
+	#[must_use]
+	pub fn from_fn<F>(function: F) -> Self
+	where
+		F: FnMut(usize) -> T,
+	{
+		let buf = (0..(W * H)).map(function).collect();
+		Self {
+			img: Img::new(buf, W, H),
+		}
+	}
+
+	#[inline]
+	#[must_use]
+	pub fn get(&self, idx: Coord) -> T {
+		self.img[idx]
+	}
+
+	#[inline]
+	pub fn set(&mut self, idx: Coord, value: T) {
+		self.img[idx] = value;
+	}
+
+	/// Draws this layer like [`World::draw`](crate::world::World::draw), using
+	/// `colour` to map values to colours.
+	pub fn draw_with<F>(&self, frame: &mut [u8], frame_width: usize, scale: usize, colour: F)
+	where
+		F: Fn(T) -> [u8; 4],
+	{
+		for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
+			let x = i % frame_width / scale;
+			let y = i / frame_width / scale;
+
+			if x < W && y < H {
+				pixel.copy_from_slice(&colour(self.img[(x, y)]));
+			}
+		}
+	}
+}
+
+impl<const W: usize, const H: usize, T: Copy + Default> Default for Layer<W, H, T> {
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+/// A handle to a layer of type `T` in [`Layers`], returned by [`Layers::add`].
+pub struct LayerId<T> {
+	index: usize,
+	_type: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for LayerId<T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<T> Copy for LayerId<T> {}
+
+impl<T> core::fmt::Debug for LayerId<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_tuple("LayerId").field(&self.index).finish()
+	}
+}
+
+/// A [`Layer`] of any value type, so that layers of different types can be
+/// stored together.
+trait AnyLayer: Any {
+	fn clone_box(&self) -> Box<dyn AnyLayer>;
+	fn as_any(&self) -> &dyn Any;
+	fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<const W: usize, const H: usize, T: Copy + 'static> AnyLayer for Layer<W, H, T> {
+	fn clone_box(&self) -> Box<dyn AnyLayer> {
+		Box::new(self.clone())
+	}
+
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any {
+		self
+	}
+}
+
+/// A collection of [`Layer`]s of possibly different value types, all of the
+/// same size as the [`World`](crate::world::World) they belong to. Layers are
+/// accessed through the typed [`LayerId`] returned when adding them.
+#[derive(Default)]
+pub struct Layers<const W: usize, const H: usize> {
+	layers: Vec<Box<dyn AnyLayer>>,
+}
+
+impl<const W: usize, const H: usize> Clone for Layers<W, H> {
+	fn clone(&self) -> Self {
+		Self {
+			layers: self.layers.iter().map(|layer| layer.clone_box()).collect(),
+		}
+	}
+}
+
+impl<const W: usize, const H: usize> Layers<W, H> {
+	/// Adds a layer, and returns the handle to access it with.
+	pub fn add<T: Copy + 'static>(&mut self, layer: Layer<W, H, T>) -> LayerId<T> {
+		self.layers.push(Box::new(layer));
+		LayerId {
+			index: self.layers.len() - 1,
+			_type: PhantomData,
+		}
+	}
+
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.layers.len()
+	}
+
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.layers.is_empty()
+	}
+
+	/// # Panics
+	/// Panics if `id` was returned by a different [`Layers`].
+	#[must_use]
+	pub fn get<T: Copy + 'static>(&self, id: LayerId<T>) -> &Layer<W, H, T> {
+		self.layers[id.index]
+			.as_any()
+			.downcast_ref()
+			.expect("layer id belongs to a different world")
+	}
+
+	/// # Panics
+	/// Panics if `id` was returned by a different [`Layers`].
+	#[must_use]
+	pub fn get_mut<T: Copy + 'static>(&mut self, id: LayerId<T>) -> &mut Layer<W, H, T> {
+		self.layers[id.index]
+			.as_any_mut()
+			.downcast_mut()
+			.expect("layer id belongs to a different world")
+	}
+}
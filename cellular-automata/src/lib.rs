@@ -1,5 +1,13 @@
+pub mod agent;
 pub mod ca;
 pub mod cpm;
+pub mod evolution;
+pub mod graph;
+pub mod layer;
+pub mod load;
+pub mod pde;
+pub mod social;
+pub mod spin;
 pub mod world;
 
 pub trait Cell: Clone + Copy + PartialEq + Eq {
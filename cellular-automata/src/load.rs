@@ -0,0 +1,114 @@
+use std::{fs::File, io, path::Path};
+
+use thiserror::Error;
+
+use crate::{world::World, Cell};
+
+#[derive(Debug, Error)]
+pub enum Error {
+	#[error(
+		"input of {0}x{1} cells does not fit in the {2}x{3} cells inside the edges of the world"
+	)]
+	TooLarge(usize, usize, usize, usize),
+
+	#[error("unknown character {0:?} at ({1}, {2})")]
+	UnknownChar(char, usize, usize),
+
+	#[error("unknown colour {0:?} at ({1}, {2})")]
+	UnknownColour([u8; 4], usize, usize),
+
+	#[error("invalid edge {0:?} on line {1}, expected two node indices")]
+	InvalidEdge(String, usize),
+
+	#[error(transparent)]
+	Png(#[from] png::DecodingError),
+
+	#[error(transparent)]
+	Io(#[from] io::Error),
+}
+
+impl<const W: usize, const H: usize, C: Cell + Default> World<W, H, C> {
+	/// Creates a [`World`] from a text grid, one line per row. `parse` converts
+	/// each character into a cell, or returns [`None`] for characters that are
+	/// not allowed. The grid is placed in the top-left corner of the interior,
+	/// inside the outermost rows and columns that [`World::convolve`] uses to
+	/// wrap the edges, and may be smaller than that (in which case the
+	/// remaining cells are default).
+	///
+	/// # Errors
+	/// Returns an error if the grid does not fit in the world, or if `parse`
+	/// does not accept a character.
+	pub fn from_text<F>(text: &str, mut parse: F) -> Result<Self, Error>
+	where
+		F: FnMut(char) -> Option<C>,
+	{
+		let lines: Vec<&str> = text.lines().collect();
+		let width = lines.iter().map(|line| line.chars().count()).max();
+		let width = width.unwrap_or_default();
+		if width > W - 2 || lines.len() > H - 2 {
+			return Err(Error::TooLarge(width, lines.len(), W - 2, H - 2));
+		}
+
+		let mut world = Self::default();
+		for (y, line) in lines.iter().enumerate() {
+			for (x, c) in line.chars().enumerate() {
+				world.img[(x + 1, y + 1)] = parse(c).ok_or(Error::UnknownChar(c, x, y))?;
+			}
+		}
+		Ok(world)
+	}
+
+	/// Creates a [`World`] from a PNG image. `parse` converts the RGBA colour of
+	/// each pixel into a cell, or returns [`None`] for colours that are not
+	/// allowed. Like with [`World::from_text`], the image is placed in the
+	/// top-left corner of the interior and may be smaller than it.
+	///
+	/// # Errors
+	/// Returns an error if the file could not be read or decoded, if the image
+	/// does not fit in the world, or if `parse` does not accept a colour.
+	pub fn from_png<P: AsRef<Path>, F>(path: P, mut parse: F) -> Result<Self, Error>
+	where
+		F: FnMut([u8; 4]) -> Option<C>,
+	{
+		let mut decoder = png::Decoder::new(File::open(path)?);
+		decoder.set_transformations(png::Transformations::normalize_to_color8());
+		let mut reader = decoder.read_info()?;
+		let mut buf = vec![0; reader.output_buffer_size()];
+		let info = reader.next_frame(&mut buf)?;
+
+		let (width, height) = (info.width as usize, info.height as usize);
+		if width > W - 2 || height > H - 2 {
+			return Err(Error::TooLarge(width, height, W - 2, H - 2));
+		}
+
+		let mut world = Self::default();
+		for (y, line) in buf.chunks_exact(info.line_size).take(height).enumerate() {
+			for x in 0..width {
+				let colour = to_rgba(info.color_type, line, x);
+				world.img[(x + 1, y + 1)] =
+					parse(colour).ok_or(Error::UnknownColour(colour, x, y))?;
+			}
+		}
+		Ok(world)
+	}
+}
+
+/// Returns the colour of the pixel at `x` in an 8-bit `line` as RGBA.
+#[must_use]
+fn to_rgba(color_type: png::ColorType, line: &[u8], x: usize) -> [u8; 4] {
+	match color_type {
+		png::ColorType::Grayscale => [line[x], line[x], line[x], 0xff],
+		png::ColorType::GrayscaleAlpha => {
+			let [v, a] = [line[x * 2], line[x * 2 + 1]];
+			[v, v, v, a]
+		}
+		png::ColorType::Rgb => [line[x * 3], line[x * 3 + 1], line[x * 3 + 2], 0xff],
+		// Indexed images are expanded to RGB(A) by the decoder
+		png::ColorType::Rgba | png::ColorType::Indexed => [
+			line[x * 4],
+			line[x * 4 + 1],
+			line[x * 4 + 2],
+			line[x * 4 + 3],
+		],
+	}
+}
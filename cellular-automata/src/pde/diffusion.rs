@@ -0,0 +1,164 @@
+use crate::{
+	layer::Layer,
+	world::{Coord, World},
+};
+
+/// What happens at the edges of the grid.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Boundary {
+	/// The edges wrap around, like in [`World::get_neighbour_idx`].
+	#[default]
+	Periodic,
+
+	/// Nothing flows across the edges (a Neumann boundary).
+	ZeroFlux,
+}
+
+impl Boundary {
+	/// Returns the coordinates of the four von Neumann neighbours of `idx`, or
+	/// [`None`] for neighbours outside a [`Boundary::ZeroFlux`] grid.
+	#[must_use]
+	pub fn neighbours<const W: usize, const H: usize>(self, idx: Coord) -> [Option<Coord>; 4] {
+		match self {
+			Boundary::Periodic => {
+				World::<W, H, bool>::get_von_neumann_neighbours_idx(idx).map(Some)
+			}
+			Boundary::ZeroFlux => {
+				let (x, y) = idx;
+				[
+					x.checked_sub(1).map(|x| (x, y)),
+					(x + 1 < W as u32).then_some((x + 1, y)),
+					y.checked_sub(1).map(|y| (x, y)),
+					(y + 1 < H as u32).then_some((x, y + 1)),
+				]
+			}
+		}
+	}
+}
+
+/// How a diffusion step is solved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Scheme {
+	/// Forward Euler. This is only stable for `coefficient * dt <= 1/4`, and
+	/// close to that limit the highest frequencies oscillate instead of being
+	/// smoothed out, so time steps are split into substeps of at most half
+	/// the limit.
+	#[default]
+	Explicit,
+
+	/// Backward Euler, solved with the given number of Jacobi iterations.
+	/// This is stable for any time step, but more expensive per step.
+	Implicit { iterations: usize },
+}
+
+/// Diffusion with linear decay, `∂u/∂t = D ∇²u - λu`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Diffusion {
+	/// The diffusion coefficient `D`, in pixels² per unit of time.
+	pub coefficient: f32,
+
+	/// The decay rate `λ`.
+	pub decay: f32,
+
+	pub scheme: Scheme,
+	pub boundary: Boundary,
+}
+
+impl Diffusion {
+	#[must_use]
+	pub fn new(coefficient: f32, decay: f32, scheme: Scheme, boundary: Boundary) -> Self {
+		Self {
+			coefficient,
+			decay,
+			scheme,
+			boundary,
+		}
+	}
+
+	/// Returns the largest time step for which the explicit scheme is stable.
+	#[must_use]
+	pub fn max_explicit_dt(&self) -> f32 {
+		1.0 / (4.0 * self.coefficient + self.decay)
+	}
+
+	/// Returns the discrete Laplacian `∇²u` at `idx`, using the 5-point
+	/// stencil.
+	#[must_use]
+	pub fn laplacian<const W: usize, const H: usize>(
+		&self,
+		layer: &Layer<W, H, f32>,
+		idx: Coord,
+	) -> f32 {
+		let centre = layer.img[idx];
+		self.boundary
+			.neighbours::<W, H>(idx)
+			.into_iter()
+			.flatten()
+			.map(|neighbour| layer.img[neighbour] - centre)
+			.sum()
+	}
+
+	/// Advances `layer` by a time step `dt`.
+	pub fn step<const W: usize, const H: usize>(&self, layer: &mut Layer<W, H, f32>, dt: f32) {
+		match self.scheme {
+			Scheme::Explicit => {
+				#[allow(clippy::cast_sign_loss)]
+				let n_substeps = (2.0 * dt / self.max_explicit_dt()).ceil().max(1.0) as usize;
+				let dt = dt / n_substeps as f32;
+				for _ in 0..n_substeps {
+					self.explicit_step(layer, dt);
+				}
+			}
+			Scheme::Implicit { iterations } => self.implicit_step(layer, dt, iterations),
+		}
+	}
+
+	fn explicit_step<const W: usize, const H: usize>(&self, layer: &mut Layer<W, H, f32>, dt: f32) {
+		let mut next = layer.clone();
+		for y in 0..H as u32 {
+			for x in 0..W as u32 {
+				let u = layer.img[(x, y)];
+				next.img[(x, y)] =
+					u + dt * (self.coefficient * self.laplacian(layer, (x, y)) - self.decay * u);
+			}
+		}
+		*layer = next;
+	}
+
+	/// Solves `(1 + λdt) u' - D dt ∇²u' = u` with Jacobi iterations, starting
+	/// from `u' = u`.
+	fn implicit_step<const W: usize, const H: usize>(
+		&self,
+		layer: &mut Layer<W, H, f32>,
+		dt: f32,
+		iterations: usize,
+	) {
+		let a = self.coefficient * dt;
+		let initial = layer.clone();
+		let mut next = layer.clone();
+		for _ in 0..iterations {
+			for y in 0..H as u32 {
+				for x in 0..W as u32 {
+					let (sum, count) = self
+						.boundary
+						.neighbours::<W, H>((x, y))
+						.into_iter()
+						.flatten()
+						.fold((0.0, 0.0), |(sum, count), neighbour| {
+							(sum + layer.img[neighbour], count + 1.0)
+						});
+					next.img[(x, y)] =
+						(initial.img[(x, y)] + a * sum) / (1.0 + a * count + self.decay * dt);
+				}
+			}
+			core::mem::swap(layer, &mut next);
+		}
+	}
+}
+
+/// Returns the sum of all values in `layer`, which diffusion without decay
+/// conserves (up to rounding).
+#[must_use]
+pub fn total<const W: usize, const H: usize>(layer: &Layer<W, H, f32>) -> f32 {
+	layer.img.pixels().sum()
+}
@@ -0,0 +1,144 @@
+//! The Gray–Scott reaction–diffusion model, `U + 2V → 3V` and `V → P`, with
+//! `U` fed in at rate `F` and `V` removed at rate `F + k`:
+//!
+//! `∂u/∂t = D_u ∇²u - uv² + F(1 - u)`
+//! `∂v/∂t = D_v ∇²v + uv² - (F + k)v`
+//!
+//! Depending on `F` and `k`, this forms spots, stripes, or self-replicating
+//! spots. See Pearson, "Complex patterns in a simple system" (1993).
+
+use rand::Rng;
+
+use crate::{
+	layer::Layer,
+	pde::{
+		colour_map,
+		diffusion::{Boundary, Diffusion, Scheme},
+	},
+};
+
+/// Parameter sets `(F, k)` producing well-known patterns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Preset {
+	/// Stable isolated spots.
+	Spots,
+
+	/// Labyrinthine stripes.
+	Stripes,
+
+	/// Spots that grow and divide, like cells undergoing mitosis.
+	Mitosis,
+
+	/// Branching coral-like growth.
+	Coral,
+}
+
+impl Preset {
+	pub const ALL: [Preset; 4] = [
+		Preset::Spots,
+		Preset::Stripes,
+		Preset::Mitosis,
+		Preset::Coral,
+	];
+
+	/// Returns the feed and kill rates `(F, k)`.
+	#[must_use]
+	pub fn params(self) -> (f32, f32) {
+		match self {
+			Preset::Spots => (0.030, 0.062),
+			Preset::Stripes => (0.029, 0.057),
+			Preset::Mitosis => (0.0367, 0.0649),
+			Preset::Coral => (0.0545, 0.062),
+		}
+	}
+}
+
+/// The concentrations of both chemicals.
+#[derive(Clone)]
+pub struct Concentrations<const W: usize, const H: usize> {
+	pub u: Layer<W, H, f32>,
+	pub v: Layer<W, H, f32>,
+}
+
+impl<const W: usize, const H: usize> Concentrations<W, H> {
+	/// Returns the homogeneous steady state `u = 1, v = 0`, with `n_seeds`
+	/// random squares of `size`×`size` pixels where `u = 1/2, v = 1/4`, and
+	/// some noise to break the symmetry.
+	#[must_use]
+	pub fn seeded<R: Rng>(n_seeds: usize, size: u32, rng: &mut R) -> Self {
+		let mut u = Layer::new(1.0_f32);
+		let mut v = Layer::new(0.0_f32);
+		for _ in 0..n_seeds {
+			let (x0, y0) = (rng.gen_range(0..W as u32), rng.gen_range(0..H as u32));
+			for dy in 0..size {
+				for dx in 0..size {
+					let idx = ((x0 + dx) % W as u32, (y0 + dy) % H as u32);
+					u.set(idx, 0.5);
+					v.set(idx, 0.25);
+				}
+			}
+		}
+		for value in v.img.pixels_mut() {
+			*value = (*value + rng.gen_range(-0.01..0.01)).max(0.0);
+		}
+		Self { u, v }
+	}
+
+	/// Draws `v` using [`colour_map`], like [`World::draw`](crate::world::World::draw).
+	pub fn draw(&self, frame: &mut [u8], frame_width: usize, scale: usize) {
+		self.v
+			.draw_with(frame, frame_width, scale, |v| colour_map(v, 0.0, 0.5));
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GrayScott {
+	/// The feed rate `F`.
+	pub feed: f32,
+
+	/// The kill rate `k`.
+	pub kill: f32,
+
+	/// The diffusion of `u`, without decay.
+	pub diffusion_u: Diffusion,
+
+	/// The diffusion of `v`, without decay.
+	pub diffusion_v: Diffusion,
+
+	/// The time step.
+	pub dt: f32,
+}
+
+impl GrayScott {
+	/// Creates a model with diffusion coefficients `D_u = 0.2`, `D_v = 0.1`
+	/// (the ratio of 2 is what matters for the patterns), explicit diffusion
+	/// with periodic edges, and `dt = 1`.
+	#[must_use]
+	pub fn new(feed: f32, kill: f32) -> Self {
+		Self {
+			feed,
+			kill,
+			diffusion_u: Diffusion::new(0.2, 0.0, Scheme::Explicit, Boundary::Periodic),
+			diffusion_v: Diffusion::new(0.1, 0.0, Scheme::Explicit, Boundary::Periodic),
+			dt: 1.0,
+		}
+	}
+
+	#[must_use]
+	pub fn preset(preset: Preset) -> Self {
+		let (feed, kill) = preset.params();
+		Self::new(feed, kill)
+	}
+
+	/// Advances the concentrations by one time step, applying the reaction
+	/// and then diffusion (operator splitting).
+	pub fn step<const W: usize, const H: usize>(&self, c: &mut Concentrations<W, H>) {
+		for (u, v) in c.u.img.pixels_mut().zip(c.v.img.pixels_mut()) {
+			let reaction = *u * *v * *v;
+			*u += self.dt * (-reaction + self.feed * (1.0 - *u));
+			*v += self.dt * (reaction - (self.feed + self.kill) * *v);
+		}
+		self.diffusion_u.step(&mut c.u, self.dt);
+		self.diffusion_v.step(&mut c.v, self.dt);
+	}
+}
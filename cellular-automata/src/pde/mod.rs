@@ -0,0 +1,30 @@
+//! Continuous fields on [`Layer`](crate::layer::Layer)s, governed by partial
+//! differential equations discretised on the lattice (with a grid spacing of
+//! one pixel).
+
+pub mod diffusion;
+pub mod gray_scott;
+
+/// Stops of the colour map used by [`colour_map`], from low to high.
+const COLOUR_STOPS: [[f32; 3]; 5] = [
+	[0x0d as f32, 0x08 as f32, 0x87 as f32],
+	[0x7e as f32, 0x03 as f32, 0xa8 as f32],
+	[0xcc as f32, 0x47 as f32, 0x78 as f32],
+	[0xf8 as f32, 0x95 as f32, 0x40 as f32],
+	[0xf0 as f32, 0xf9 as f32, 0x21 as f32],
+];
+
+/// Maps `value` in `min..=max` to a colour, from dark blue through purple and
+/// orange to yellow. Values outside the range are clamped.
+#[must_use]
+#[allow(clippy::cast_sign_loss)]
+pub fn colour_map(value: f32, min: f32, max: f32) -> [u8; 4] {
+	let t = ((value - min) / (max - min)).clamp(0.0, 1.0);
+	let t = if t.is_nan() { 0.0 } else { t };
+	let pos = t * (COLOUR_STOPS.len() - 1) as f32;
+	let i = (pos as usize).min(COLOUR_STOPS.len() - 2);
+	let frac = pos - i as f32;
+	let [a, b] = [COLOUR_STOPS[i], COLOUR_STOPS[i + 1]];
+	let channel = |c: usize| (a[c] + (b[c] - a[c]) * frac).round() as u8;
+	[channel(0), channel(1), channel(2), 0xff]
+}
@@ -0,0 +1,17 @@
+//! Lattice models of social dynamics, where the cells are individuals that
+//! move to or copy from arbitrary places in the world.
+
+pub mod schelling;
+pub mod voter;
+
+/// Distinct colours for up to eight groups of individuals.
+const PALETTE: [[u8; 4]; 8] = [
+	[0xe6, 0x39, 0x46, 0xff],
+	[0x45, 0x7b, 0x9d, 0xff],
+	[0xf4, 0xa2, 0x61, 0xff],
+	[0x2a, 0x9d, 0x8f, 0xff],
+	[0x9b, 0x5d, 0xe5, 0xff],
+	[0xe9, 0xc4, 0x6a, 0xff],
+	[0x26, 0x46, 0x53, 0xff],
+	[0xff, 0x99, 0xc8, 0xff],
+];
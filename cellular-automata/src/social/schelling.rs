@@ -0,0 +1,205 @@
+//! Schelling's segregation model: agents of several types live on a lattice
+//! with some empty cells, and move away when too few of their neighbours are
+//! of their own type. Even mild preferences lead to strongly segregated
+//! neighbourhoods. See Schelling, "Dynamic models of segregation" (1971).
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+	social::PALETTE,
+	world::{Coord, World},
+	Cell,
+};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub enum Resident {
+	#[default]
+	Empty,
+	Agent(u8),
+}
+
+impl Cell for Resident {
+	fn colour(&self) -> [u8; 4] {
+		match self {
+			Resident::Empty => [0xff, 0xff, 0xff, 0xff],
+			Resident::Agent(t) => PALETTE[*t as usize % PALETTE.len()],
+		}
+	}
+}
+
+/// Where unhappy agents move to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Relocation {
+	/// A uniformly random empty cell, as in Schelling's original model.
+	#[default]
+	Random,
+
+	/// A random empty cell where the agent would be happy. Agents that cannot
+	/// find one stay where they are.
+	Happy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schelling {
+	/// The minimum fraction of occupied neighbours that must be of the same
+	/// type for an agent to be happy.
+	pub tolerance: f32,
+
+	pub relocation: Relocation,
+}
+
+impl Schelling {
+	#[must_use]
+	pub fn new(tolerance: f32, relocation: Relocation) -> Self {
+		Self {
+			tolerance,
+			relocation,
+		}
+	}
+
+	/// Returns a world where every cell is empty with probability `vacancy`,
+	/// and otherwise an agent of a uniformly random type in `0..n_types`.
+	#[must_use]
+	pub fn random_world<const W: usize, const H: usize, R: Rng>(
+		n_types: u8,
+		vacancy: f64,
+		rng: &mut R,
+	) -> World<W, H, Resident> {
+		World::from_fn(|_| {
+			if rng.gen_bool(vacancy) {
+				Resident::Empty
+			} else {
+				Resident::Agent(rng.gen_range(0..n_types))
+			}
+		})
+	}
+
+	/// Returns whether the agent at `idx` is happy with its (Moore)
+	/// neighbourhood. Empty cells and agents without neighbours are happy.
+	#[must_use]
+	pub fn is_happy<const W: usize, const H: usize>(
+		&self,
+		world: &World<W, H, Resident>,
+		idx: Coord,
+	) -> bool {
+		let cell = world.img[idx];
+		if cell == Resident::Empty {
+			return true;
+		}
+		let (similar, occupied) = similar_neighbours(world, idx);
+		occupied == 0 || similar as f32 >= self.tolerance * occupied as f32
+	}
+
+	/// Returns the fraction of agents that are unhappy.
+	#[must_use]
+	pub fn unhappy_fraction<const W: usize, const H: usize>(
+		&self,
+		world: &World<W, H, Resident>,
+	) -> f32 {
+		let agents = world.positions(|cell| cell != Resident::Empty);
+		let unhappy = agents
+			.iter()
+			.filter(|&&idx| !self.is_happy(world, idx))
+			.count();
+		unhappy as f32 / agents.len().max(1) as f32
+	}
+
+	/// Lets every agent that is unhappy at the start of the step move, in
+	/// random order, to an empty cell chosen according to `self.relocation`.
+	/// Agents that have become happy in the meantime stay. Returns the number
+	/// of agents that moved.
+	pub fn step<const W: usize, const H: usize, R: Rng>(
+		&self,
+		world: &mut World<W, H, Resident>,
+		rng: &mut R,
+	) -> usize {
+		let mut empty = world.positions(|cell| cell == Resident::Empty);
+		let mut unhappy: Vec<Coord> = world
+			.positions(|cell| cell != Resident::Empty)
+			.into_iter()
+			.filter(|&idx| !self.is_happy(world, idx))
+			.collect();
+		unhappy.shuffle(rng);
+
+		let mut moved = 0;
+		for idx in unhappy {
+			if empty.is_empty() || self.is_happy(world, idx) {
+				continue;
+			}
+			if let Some(i) = self.choose_destination(world, idx, &empty, rng) {
+				world.swap(idx, empty[i]);
+				// The old position of the agent is now empty
+				empty[i] = idx;
+				moved += 1;
+			}
+		}
+		moved
+	}
+
+	/// Returns the index into `empty` of the cell the agent at `idx` moves to.
+	fn choose_destination<const W: usize, const H: usize, R: Rng>(
+		self,
+		world: &mut World<W, H, Resident>,
+		idx: Coord,
+		empty: &[Coord],
+		rng: &mut R,
+	) -> Option<usize> {
+		match self.relocation {
+			Relocation::Random => Some(rng.gen_range(0..empty.len())),
+			Relocation::Happy => {
+				let mut candidates: Vec<usize> = (0..empty.len()).collect();
+				candidates.shuffle(rng);
+				candidates.into_iter().find(|&i| {
+					// Try the move, to account for the agent leaving its old
+					// neighbourhood
+					world.swap(idx, empty[i]);
+					let happy = self.is_happy(world, empty[i]);
+					world.swap(idx, empty[i]);
+					happy
+				})
+			}
+		}
+	}
+
+	/// Runs until no agent moves any more, or for at most `max_steps` steps.
+	/// Returns the number of steps until the world settled, or [`None`] if it
+	/// did not.
+	pub fn run_until_settled<const W: usize, const H: usize, R: Rng>(
+		&self,
+		world: &mut World<W, H, Resident>,
+		max_steps: usize,
+		rng: &mut R,
+	) -> Option<usize> {
+		(0..max_steps).find(|_| self.step(world, rng) == 0)
+	}
+}
+
+/// Returns the number of (Moore) neighbours of the agent at `idx` that are of
+/// the same type, and the number of neighbours that are not empty.
+fn similar_neighbours<const W: usize, const H: usize>(
+	world: &World<W, H, Resident>,
+	idx: Coord,
+) -> (usize, usize) {
+	let cell = world.img[idx];
+	let neighbours = world.get_neighbours(idx);
+	let occupied = neighbours.iter().filter(|&&n| n != Resident::Empty).count();
+	let similar = neighbours.iter().filter(|&&n| n == cell).count();
+	(similar, occupied)
+}
+
+/// Returns the segregation index: the mean fraction of occupied neighbours
+/// that are of the same type, over all agents that have neighbours. For
+/// randomly placed agents of `n` equally common types this is about `1 / n`,
+/// for fully segregated agents it approaches 1.
+#[must_use]
+pub fn segregation_index<const W: usize, const H: usize>(world: &World<W, H, Resident>) -> f32 {
+	let fractions: Vec<f32> = world
+		.positions(|cell| cell != Resident::Empty)
+		.into_iter()
+		.filter_map(|idx| {
+			let (similar, occupied) = similar_neighbours(world, idx);
+			(occupied > 0).then(|| similar as f32 / occupied as f32)
+		})
+		.collect();
+	fractions.iter().sum::<f32>() / fractions.len().max(1) as f32
+}
@@ -0,0 +1,112 @@
+//! The voter model: every individual holds one of several opinions, and
+//! repeatedly adopts the opinion of a random neighbour. On a finite lattice
+//! this always ends in consensus, but coarsening is slow in two dimensions.
+
+use rand::Rng;
+
+use crate::{social::PALETTE, world::World, Cell};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct Opinion(pub u8);
+
+impl Cell for Opinion {
+	fn colour(&self) -> [u8; 4] {
+		PALETTE[self.0 as usize % PALETTE.len()]
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Voter {
+	/// The probability that an individual adopts a random opinion instead of
+	/// copying its neighbour (the noisy voter model). With noise, consensus
+	/// is never permanent.
+	pub noise: f32,
+
+	/// The number of opinions, used for noise.
+	pub n_opinions: u8,
+}
+
+impl Default for Voter {
+	/// Two opinions, without noise.
+	fn default() -> Self {
+		Self::new(2, 0.0)
+	}
+}
+
+impl Voter {
+	#[must_use]
+	pub fn new(n_opinions: u8, noise: f32) -> Self {
+		Self { noise, n_opinions }
+	}
+
+	/// Returns a world where every individual holds a uniformly random opinion.
+	#[must_use]
+	pub fn random_world<const W: usize, const H: usize, R: Rng>(
+		&self,
+		rng: &mut R,
+	) -> World<W, H, Opinion> {
+		World::from_fn(|_| Opinion(rng.gen_range(0..self.n_opinions)))
+	}
+
+	/// Performs one sweep of `W * H` updates, in each of which a random
+	/// individual adopts the opinion of a random neighbour, or with
+	/// probability `noise` a random opinion (even if it agrees with all of its
+	/// neighbours).
+	pub fn step<const W: usize, const H: usize>(&self, world: &mut World<W, H, Opinion>) {
+		let mut rng = rand::thread_rng();
+		world.metropolis_flip(|world, _, idx| {
+			if self.noise > 0.0 && rng.gen::<f32>() < self.noise {
+				Opinion(rng.gen_range(0..self.n_opinions))
+			} else {
+				let neighbours = World::<W, H, Opinion>::get_neighbours_idx(idx);
+				world.img[neighbours[rng.gen_range(0..neighbours.len())]]
+			}
+		});
+	}
+
+	/// Runs until all individuals agree, or for at most `max_sweeps` sweeps.
+	/// Returns the number of sweeps until consensus, or [`None`] if it was not
+	/// reached.
+	pub fn consensus_time<const W: usize, const H: usize>(
+		&self,
+		world: &mut World<W, H, Opinion>,
+		max_sweeps: usize,
+	) -> Option<usize> {
+		(0..=max_sweeps).find(|&sweep| {
+			let done = consensus(world).is_some();
+			if !done && sweep < max_sweeps {
+				self.step(world);
+			}
+			done
+		})
+	}
+}
+
+/// Returns the opinion of all individuals, if they all agree.
+#[must_use]
+pub fn consensus<const W: usize, const H: usize>(world: &World<W, H, Opinion>) -> Option<Opinion> {
+	let first = *world.img.buf().first()?;
+	world
+		.img
+		.pixels()
+		.all(|cell| cell == first)
+		.then_some(first)
+}
+
+/// Returns the fraction of pairs of (von Neumann) neighbours that disagree.
+/// This decays towards 0 as the opinions coarsen.
+#[must_use]
+pub fn interface_density<const W: usize, const H: usize>(world: &World<W, H, Opinion>) -> f32 {
+	let mut disagreeing = 0;
+	for y in 0..H as u32 {
+		for x in 0..W as u32 {
+			let cell = world.img[(x, y)];
+			// Count every pair once, by only looking right and down
+			let right = World::<W, H, Opinion>::get_neighbour_idx((x, y), (1, 0));
+			let down = World::<W, H, Opinion>::get_neighbour_idx((x, y), (0, 1));
+			disagreeing +=
+				usize::from(world.img[right] != cell) + usize::from(world.img[down] != cell);
+		}
+	}
+	disagreeing as f32 / (2 * W * H) as f32
+}
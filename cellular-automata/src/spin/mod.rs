@@ -0,0 +1,256 @@
+//! Ising and q-state Potts spin models, simulated with single spin-flip
+//! Metropolis dynamics and Wolff cluster updates. Spins interact with their
+//! four von Neumann neighbours on the full `W`×`H` torus, so the measured
+//! critical temperature can be compared with the exact one (see
+//! [`Potts::critical_temperature`]).
+
+pub mod observables;
+
+use rand::Rng;
+
+use crate::{
+	world::{Coord, World},
+	Cell,
+};
+
+/// The state of a single spin, in `0..q`. For the Ising model, `Spin(0)` is
+/// spin up (+1) and `Spin(1)` is spin down (-1).
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct Spin(pub u8);
+
+const PALETTE: [[u8; 4]; 8] = [
+	[0xff, 0xff, 0xff, 0xff],
+	[0x00, 0x00, 0x00, 0xff],
+	[0xe6, 0x39, 0x46, 0xff],
+	[0x45, 0x7b, 0x9d, 0xff],
+	[0xf4, 0xa2, 0x61, 0xff],
+	[0x2a, 0x9d, 0x8f, 0xff],
+	[0x9b, 0x5d, 0xe5, 0xff],
+	[0xe9, 0xc4, 0x6a, 0xff],
+];
+
+impl Cell for Spin {
+	fn colour(&self) -> [u8; 4] {
+		PALETTE[self.0 as usize % PALETTE.len()]
+	}
+}
+
+/// The q-state Potts model with Hamiltonian
+/// `E = -J Σ δ(s_i, s_j) - h Σ δ(s_i, 0)`, where the first sum runs over all
+/// pairs of neighbours. The external field `h` favours state 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Potts {
+	/// The number of states.
+	pub q: u8,
+
+	/// The coupling constant `J`, positive for a ferromagnet.
+	pub coupling: f64,
+
+	/// The external field `h`.
+	pub field: f64,
+
+	/// The temperature `T`, in units where Boltzmann's constant is 1.
+	pub temperature: f64,
+
+	/// A constant energy per site, so that [`Potts::ising`] reports energies in
+	/// the usual Ising convention.
+	offset: f64,
+}
+
+impl Potts {
+	/// Creates a q-state Potts model.
+	///
+	/// # Panics
+	/// Panics if `q` is less than 2.
+	#[must_use]
+	pub fn new(q: u8, coupling: f64, field: f64, temperature: f64) -> Self {
+		assert!(q >= 2, "a Potts model needs at least 2 states");
+		Self {
+			q,
+			coupling,
+			field,
+			temperature,
+			offset: 0.0,
+		}
+	}
+
+	/// Creates the Ising model `E = -J Σ s_i s_j - h Σ s_i` with `s_i = ±1`.
+	/// Since `s_i s_j = 2 δ(s_i, s_j) - 1`, this is the 2-state Potts model
+	/// with coupling `2J` and field `2h`, shifted by a constant energy.
+	#[must_use]
+	pub fn ising(coupling: f64, field: f64, temperature: f64) -> Self {
+		Self {
+			q: 2,
+			coupling: 2.0 * coupling,
+			field: 2.0 * field,
+			temperature,
+			offset: 2.0 * coupling + field,
+		}
+	}
+
+	/// Returns the exact critical temperature of the model on the square
+	/// lattice without external field, `T_c = J / ln(1 + √q)`. For the Ising
+	/// model this is `2J / ln(1 + √2) ≈ 2.269 J`. The transition is continuous
+	/// for `q <= 4` and first order for `q > 4`.
+	#[must_use]
+	pub fn critical_temperature(&self) -> f64 {
+		self.coupling / (1.0 + f64::from(self.q).sqrt()).ln()
+	}
+
+	/// Returns a world with all spins in state 0 (the ordered ground state for
+	/// a ferromagnet).
+	#[must_use]
+	pub fn ordered<const W: usize, const H: usize>(&self) -> World<W, H, Spin> {
+		World::default()
+	}
+
+	/// Returns a world with every spin in a uniformly random state (the
+	/// infinite-temperature state).
+	#[must_use]
+	pub fn random<const W: usize, const H: usize, R: Rng>(&self, rng: &mut R) -> World<W, H, Spin> {
+		World::from_fn(|_| Spin(rng.gen_range(0..self.q)))
+	}
+
+	/// Returns the energy of the bonds and field term of the spin at `idx`, if
+	/// it were in state `spin`.
+	#[must_use]
+	fn local_energy<const W: usize, const H: usize>(
+		&self,
+		world: &World<W, H, Spin>,
+		idx: Coord,
+		spin: Spin,
+	) -> f64 {
+		let n_equal = world
+			.get_von_neumann_neighbours(idx)
+			.iter()
+			.filter(|&&neighbour| neighbour == spin)
+			.count();
+		-self.coupling * n_equal as f64 - self.field_energy(spin)
+	}
+
+	/// Returns the (negated) contribution of the external field for a single
+	/// spin.
+	#[inline]
+	fn field_energy(&self, spin: Spin) -> f64 {
+		if spin.0 == 0 {
+			self.field
+		} else {
+			0.0
+		}
+	}
+
+	/// Returns a random state different from `spin`.
+	fn other_state<R: Rng>(&self, spin: Spin, rng: &mut R) -> Spin {
+		let offset = rng.gen_range(1..self.q);
+		Spin((spin.0 + offset) % self.q)
+	}
+
+	/// Returns the total energy of `world`.
+	#[must_use]
+	pub fn energy<const W: usize, const H: usize>(&self, world: &World<W, H, Spin>) -> f64 {
+		let mut energy = 0.0;
+		for y in 0..H as u32 {
+			for x in 0..W as u32 {
+				let spin = world.img[(x, y)];
+				// Count every bond once, by only looking right and down
+				let right = World::<W, H, Spin>::get_neighbour_idx((x, y), (1, 0));
+				let down = World::<W, H, Spin>::get_neighbour_idx((x, y), (0, 1));
+				let n_equal =
+					u8::from(world.img[right] == spin) + u8::from(world.img[down] == spin);
+				energy += -self.coupling * f64::from(n_equal) - self.field_energy(spin);
+			}
+		}
+		energy + self.offset * (W * H) as f64
+	}
+
+	/// Returns the order parameter `(q * n_max / N - 1) / (q - 1)`, where
+	/// `n_max` is the number of spins in the most common state. This is 1 for
+	/// a fully ordered world and close to 0 for a disordered one. For the Ising
+	/// model this equals the absolute magnetisation per spin `|m|`.
+	#[must_use]
+	pub fn magnetisation<const W: usize, const H: usize>(&self, world: &World<W, H, Spin>) -> f64 {
+		let mut counts = vec![0_usize; self.q as usize];
+		for spin in world.img.pixels() {
+			counts[spin.0 as usize] += 1;
+		}
+		let n_max = counts.into_iter().max().unwrap_or_default();
+		let q = f64::from(self.q);
+		(q * n_max as f64 / (W * H) as f64 - 1.0) / (q - 1.0)
+	}
+
+	/// Performs one Monte Carlo sweep of `W * H` single spin-flip attempts.
+	/// Each attempt proposes a random different state, which is accepted with
+	/// the Metropolis probability `min(1, exp(-ΔE / T))`.
+	pub fn metropolis_sweep<const W: usize, const H: usize>(&self, world: &mut World<W, H, Spin>) {
+		let mut rng = rand::thread_rng();
+		world.metropolis_flip(|world, spin, idx| {
+			let proposal = self.other_state(spin, &mut rng);
+			let delta_e =
+				self.local_energy(world, idx, proposal) - self.local_energy(world, idx, spin);
+			if delta_e <= 0.0 || rng.gen::<f64>() < (-delta_e / self.temperature).exp() {
+				proposal
+			} else {
+				spin
+			}
+		});
+	}
+
+	/// Performs a single Wolff cluster update: grows a cluster of equal spins
+	/// from a random seed, adding each equal neighbour with probability
+	/// `1 - exp(-J / T)`, and moves the whole cluster to a random other state.
+	/// With an external field, the move is accepted with probability
+	/// `min(1, exp(-ΔE_field / T))`. Returns the size of the cluster, or 0 if
+	/// the move was rejected.
+	pub fn wolff_update<const W: usize, const H: usize>(
+		&self,
+		world: &mut World<W, H, Spin>,
+	) -> usize {
+		let mut rng = rand::thread_rng();
+		let p_add = 1.0 - (-self.coupling / self.temperature).exp();
+
+		let seed = (rng.gen_range(0..W as u32), rng.gen_range(0..H as u32));
+		let old = world.img[seed];
+		let new = self.other_state(old, &mut rng);
+
+		let mut in_cluster = vec![false; W * H];
+		in_cluster[seed.1 as usize * W + seed.0 as usize] = true;
+		let mut cluster = vec![seed];
+		let mut stack = vec![seed];
+		while let Some(idx) = stack.pop() {
+			for neighbour in World::<W, H, Spin>::get_von_neumann_neighbours_idx(idx) {
+				let flat = neighbour.1 as usize * W + neighbour.0 as usize;
+				if !in_cluster[flat] && world.img[neighbour] == old && rng.gen::<f64>() < p_add {
+					in_cluster[flat] = true;
+					cluster.push(neighbour);
+					stack.push(neighbour);
+				}
+			}
+		}
+
+		let delta_e = (self.field_energy(old) - self.field_energy(new)) * cluster.len() as f64;
+		if delta_e > 0.0 && rng.gen::<f64>() >= (-delta_e / self.temperature).exp() {
+			return 0;
+		}
+		for idx in &cluster {
+			world.img[*idx] = new;
+		}
+		cluster.len()
+	}
+
+	/// Performs Wolff cluster updates until about `W * H` spins have been
+	/// moved, which makes it comparable to one Metropolis sweep. Returns the
+	/// number of cluster updates.
+	pub fn wolff_sweep<const W: usize, const H: usize>(
+		&self,
+		world: &mut World<W, H, Spin>,
+	) -> usize {
+		let mut moved = 0;
+		let mut n_updates = 0;
+		// Also count rejected updates, so that this ends in a strong field
+		while moved < W * H && n_updates < W * H {
+			moved += self.wolff_update(world);
+			n_updates += 1;
+		}
+		n_updates
+	}
+}
@@ -0,0 +1,159 @@
+use std::io::{self, Write};
+
+use crate::{
+	spin::{Potts, Spin},
+	world::World,
+};
+
+/// How the spins are updated between measurements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Update {
+	/// Single spin-flip Metropolis sweeps, see [`Potts::metropolis_sweep`].
+	#[default]
+	Metropolis,
+
+	/// Wolff cluster updates, see [`Potts::wolff_sweep`]. These suffer much
+	/// less from critical slowing down near the critical temperature.
+	Wolff,
+}
+
+impl Update {
+	/// Performs one sweep of this update on `world`.
+	pub fn sweep<const W: usize, const H: usize>(
+		self,
+		model: &Potts,
+		world: &mut World<W, H, Spin>,
+	) {
+		match self {
+			Update::Metropolis => model.metropolis_sweep(world),
+			Update::Wolff => {
+				model.wolff_sweep(world);
+			}
+		}
+	}
+}
+
+/// Thermal averages at a single temperature, per spin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+	pub temperature: f64,
+
+	/// The mean energy per spin `<E> / N`.
+	pub energy: f64,
+
+	/// The mean order parameter `<m>`, see [`Potts::magnetisation`].
+	pub magnetisation: f64,
+
+	/// The magnetic susceptibility per spin `N (<m²> - <m>²) / T`.
+	pub susceptibility: f64,
+
+	/// The specific heat per spin `(<E²> - <E>²) / (N T²)`.
+	pub specific_heat: f64,
+}
+
+/// Equilibrates `world` for `equilibration` sweeps, then takes `samples`
+/// measurements, one after every sweep.
+pub fn measure<const W: usize, const H: usize>(
+	model: &Potts,
+	world: &mut World<W, H, Spin>,
+	update: Update,
+	equilibration: usize,
+	samples: usize,
+) -> Measurement {
+	for _ in 0..equilibration {
+		update.sweep(model, world);
+	}
+
+	let (mut energy, mut energy_sq, mut order, mut order_sq) = (0.0, 0.0, 0.0, 0.0);
+	for _ in 0..samples {
+		update.sweep(model, world);
+		let e = model.energy(world);
+		let m = model.magnetisation(world);
+		energy += e;
+		energy_sq += e * e;
+		order += m;
+		order_sq += m * m;
+	}
+
+	let n = (W * H) as f64;
+	let samples = samples.max(1) as f64;
+	let (energy, energy_sq) = (energy / samples, energy_sq / samples);
+	let (order, order_sq) = (order / samples, order_sq / samples);
+	let t = model.temperature;
+	Measurement {
+		temperature: t,
+		energy: energy / n,
+		magnetisation: order,
+		susceptibility: n * (order_sq - order * order) / t,
+		specific_heat: (energy_sq - energy * energy) / (n * t * t),
+	}
+}
+
+/// Measures the model at each of `temperatures` in turn, continuing from the
+/// state at the previous temperature (so sweeping from low to high
+/// temperature starts from an ordered world, and the other way around from a
+/// disordered one).
+pub fn temperature_sweep<const W: usize, const H: usize>(
+	model: &Potts,
+	world: &mut World<W, H, Spin>,
+	temperatures: &[f64],
+	update: Update,
+	equilibration: usize,
+	samples: usize,
+) -> Vec<Measurement> {
+	temperatures
+		.iter()
+		.map(|&temperature| {
+			let model = Potts {
+				temperature,
+				..*model
+			};
+			measure(&model, world, update, equilibration, samples)
+		})
+		.collect()
+}
+
+/// Returns `n` evenly spaced temperatures from `from` to `to` (inclusive).
+#[must_use]
+pub fn linspace(from: f64, to: f64, n: usize) -> Vec<f64> {
+	match n {
+		0 => Vec::new(),
+		1 => vec![from],
+		_ => (0..n)
+			.map(|i| from + (to - from) * i as f64 / (n - 1) as f64)
+			.collect(),
+	}
+}
+
+/// Returns the temperature at which the specific heat peaks, an estimate of
+/// the critical temperature in a finite world.
+#[must_use]
+pub fn specific_heat_peak(measurements: &[Measurement]) -> Option<f64> {
+	measurements
+		.iter()
+		.max_by(|a, b| a.specific_heat.total_cmp(&b.specific_heat))
+		.map(|m| m.temperature)
+}
+
+/// Writes the measurements as CSV, with the columns
+/// `temperature,energy,magnetisation,susceptibility,specific_heat`.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn write_measurements_csv<Wr: Write>(
+	out: &mut Wr,
+	measurements: &[Measurement],
+) -> io::Result<()> {
+	writeln!(
+		out,
+		"temperature,energy,magnetisation,susceptibility,specific_heat"
+	)?;
+	for m in measurements {
+		writeln!(
+			out,
+			"{},{},{},{},{}",
+			m.temperature, m.energy, m.magnetisation, m.susceptibility, m.specific_heat
+		)?;
+	}
+	Ok(())
+}
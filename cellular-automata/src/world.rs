@@ -2,18 +2,24 @@ use imgref::Img;
 use loop9::loop9_img;
 use rand::prelude::*;
 
-use crate::Cell;
+use crate::{layer::Layers, Cell};
 
 pub type Coord = (u32, u32);
 
+#[derive(Clone)]
 pub struct World<const W: usize, const H: usize, C: Cell> {
 	pub img: Img<Vec<C>>,
+
+	/// Auxiliary per-pixel quantities, such as chemical concentrations, that
+	/// rules can read alongside the cell states.
+	pub layers: Layers<W, H>,
 }
 
 impl<const W: usize, const H: usize, C: Cell + Default> Default for World<W, H, C> {
 	fn default() -> Self {
 		Self {
 			img: Img::new(vec![C::default(); W * H], W, H),
+			layers: Layers::default(),
 		}
 	}
 }
@@ -27,16 +33,27 @@ impl<const W: usize, const H: usize, C: Cell> World<W, H, C> {
 		let buf = (0..(W * H)).map(function).collect();
 		Self {
 			img: Img::new(buf, W, H),
+			layers: Layers::default(),
 		}
 	}
 
 	pub fn draw(&self, frame: &mut [u8], frame_width: usize, scale: usize) {
+		self.draw_with(frame, frame_width, scale, |_, _, cell| cell.colour());
+	}
+
+	/// Like [`World::draw`], but with `colour` determining the colour of each
+	/// pixel, so that it can also depend on the layers.
+	pub fn draw_with<F>(&self, frame: &mut [u8], frame_width: usize, scale: usize, colour: F)
+	where
+		F: Fn(&Self, Coord, C) -> [u8; 4],
+	{
 		for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
 			let x = i % frame_width / scale;
 			let y = i / frame_width / scale;
 
 			if x < W && y < H {
-				pixel.copy_from_slice(&self.img[(x, y)].colour());
+				let idx = (x as u32, y as u32);
+				pixel.copy_from_slice(&colour(self, idx, self.img[idx]));
 			}
 		}
 	}
@@ -44,6 +61,25 @@ impl<const W: usize, const H: usize, C: Cell> World<W, H, C> {
 	pub fn convolve<F>(&mut self, mut rule: F)
 	where
 		F: FnMut([C; 9]) -> C,
+	{
+		self.convolve_indexed(|_x, _y, neighbourhood| rule(neighbourhood));
+	}
+
+	/// Returns the rows of the world without the outermost rows and columns,
+	/// which [`World::convolve`] uses to wrap the edges.
+	pub fn interior_rows(&self) -> impl Iterator<Item = &[C]> {
+		self.img
+			.rows()
+			.skip(1)
+			.take(H - 2)
+			.map(|row| &row[1..W - 1])
+	}
+
+	/// Like [`World::convolve`], but also passes the coordinates of the cell to
+	/// `rule`.
+	pub fn convolve_indexed<F>(&mut self, mut rule: F)
+	where
+		F: FnMut(usize, usize, [C; 9]) -> C,
 	{
 		self.wrap_edges();
 
@@ -55,14 +91,17 @@ impl<const W: usize, const H: usize, C: Cell> World<W, H, C> {
 				mid.prev, mid.curr, mid.next,
 				bot.prev, bot.curr, bot.next,
 			];
-			new_img[(x, y)] = rule(neighbourhood);
+			new_img[(x, y)] = rule(x, y, neighbourhood);
 		});
 		self.img = new_img;
 	}
 
+	/// Performs `W * H` copy attempts from a random cell to a random neighbour
+	/// with a different state. `update` returns the new state of the
+	/// neighbour, and may also modify the world (for example its layers).
 	pub fn metropolis<F>(&mut self, mut update: F)
 	where
-		F: FnMut(&Self, C, C, Coord, Coord) -> C,
+		F: FnMut(&mut Self, C, C, Coord, Coord) -> C,
 	{
 		let mut rng = rand::thread_rng();
 		for _ in 0..W * H {
@@ -77,6 +116,45 @@ impl<const W: usize, const H: usize, C: Cell> World<W, H, C> {
 		}
 	}
 
+	/// Performs `W * H` single-site update attempts at random sites. `update`
+	/// receives the current state of the site and returns its new state (which
+	/// may be the same, for rejected attempts).
+	pub fn metropolis_flip<F>(&mut self, mut update: F)
+	where
+		F: FnMut(&Self, C, Coord) -> C,
+	{
+		let mut rng = rand::thread_rng();
+		for _ in 0..W * H {
+			let idx = (rng.gen_range(0..W as u32), rng.gen_range(0..H as u32));
+			self.img[idx] = update(self, self.img[idx], idx);
+		}
+	}
+
+	/// Swaps the cells at `a` and `b`, which need not be neighbours.
+	#[inline]
+	pub fn swap(&mut self, a: Coord, b: Coord) {
+		let cell_a = self.img[a];
+		self.img[a] = self.img[b];
+		self.img[b] = cell_a;
+	}
+
+	/// Returns the coordinates of all cells for which `filter` returns `true`.
+	#[must_use]
+	pub fn positions<F>(&self, mut filter: F) -> Vec<Coord>
+	where
+		F: FnMut(C) -> bool,
+	{
+		let mut positions = Vec::new();
+		for (y, row) in self.img.rows().enumerate() {
+			for (x, &cell) in row.iter().enumerate() {
+				if filter(cell) {
+					positions.push((x as u32, y as u32));
+				}
+			}
+		}
+		positions
+	}
+
 	#[inline]
 	#[must_use]
 	pub fn get_cell(&self, idx: Coord) -> C {
@@ -98,8 +176,29 @@ impl<const W: usize, const H: usize, C: Cell> World<W, H, C> {
 		]
 	}
 
+	/// Returns the four von Neumann neighbours (left, right, up, down) of the
+	/// cell at `cell_idx`.
 	#[must_use]
-	fn get_neighbours_idx(cell_idx: Coord) -> [Coord; 8] {
+	pub fn get_von_neumann_neighbours(&self, cell_idx: Coord) -> [C; 4] {
+		Self::get_von_neumann_neighbours_idx(cell_idx).map(|idx| self.img[idx])
+	}
+
+	/// Returns the coordinates of the four von Neumann neighbours (left, right,
+	/// up, down) of `cell_idx`.
+	#[must_use]
+	pub fn get_von_neumann_neighbours_idx(cell_idx: Coord) -> [Coord; 4] {
+		[
+			Self::get_neighbour_idx(cell_idx, (-1, 0)),
+			Self::get_neighbour_idx(cell_idx, (1, 0)),
+			Self::get_neighbour_idx(cell_idx, (0, -1)),
+			Self::get_neighbour_idx(cell_idx, (0, 1)),
+		]
+	}
+
+	/// Returns the coordinates of the 8 neighbours of `cell_idx`, in the same
+	/// order as [`World::get_neighbours`].
+	#[must_use]
+	pub fn get_neighbours_idx(cell_idx: Coord) -> [Coord; 8] {
 		[
 			Self::get_neighbour_idx(cell_idx, (-1, -1)),
 			Self::get_neighbour_idx(cell_idx, (0, -1)),
@@ -113,21 +212,37 @@ impl<const W: usize, const H: usize, C: Cell> World<W, H, C> {
 		]
 	}
 
+	/// Returns the coordinate at `offset` from `cell_idx`, wrapping around the
+	/// edges of the world. The offset may not be larger than the size of the
+	/// world.
 	#[inline]
 	#[must_use]
-	fn get_neighbour_idx(cell_idx: Coord, offset: (i32, i32)) -> Coord {
+	pub fn get_neighbour_idx(cell_idx: Coord, offset: (i32, i32)) -> Coord {
+		// Add the size of the world first, so that negative offsets at the
+		// left/top edges wrap around instead of saturating at 0 (which made
+		// pixels at those edges pick themselves instead of the neighbour on
+		// the other side, for example in `metropolis`)
 		(
-			cell_idx
-				.0
+			(cell_idx.0 + W as u32)
 				.saturating_add_signed(offset.0)
 				.rem_euclid(W as u32),
-			cell_idx
-				.1
+			(cell_idx.1 + H as u32)
 				.saturating_add_signed(offset.1)
 				.rem_euclid(H as u32),
 		)
 	}
 
+	/// Returns the shortest displacement from `from` to `to`, wrapping around
+	/// the edges of the world.
+	#[must_use]
+	pub fn displacement(from: Coord, to: Coord) -> (i32, i32) {
+		let wrap = |a: u32, b: u32, size: usize| {
+			let size = i64::from(size as u32);
+			((i64::from(a) - i64::from(b) + size + size / 2).rem_euclid(size) - size / 2) as i32
+		};
+		(wrap(to.0, from.0, W), wrap(to.1, from.1, H))
+	}
+
 	/// Chooses a random coordinate neighbouring the cell at `cell_idx`.
 	fn random_neighbour_idx<R: Rng>(cell_idx: Coord, rng: &mut R) -> Coord {
 		Self::get_neighbours_idx(cell_idx)[rng.gen_range(0..8)]